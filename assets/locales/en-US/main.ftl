@@ -0,0 +1,139 @@
+# Reference locale: every other locale has to define the same messages, which the build checks.
+# Numbers passed to the messages are already formatted with the separators below.
+
+language-name = English
+digit-group-separator = {","}
+decimal-separator = {"."}
+
+## Loading screen
+
+loading-title = Loading
+loading-failed = Failed to load game assets
+loading-failed-hint = Check that the files exist in the assets directory and restart the game.
+
+## Menus
+
+menu-title = Main Menu
+menu-play = Play
+menu-hangar = Hangar
+menu-stats = Stats
+menu-settings = Settings
+menu-quit = Quit
+menu-back = Back
+
+hangar-select = Select
+hangar-hull = { $name }{ $equipped ->
+        [yes] {" "}(equipped)
+       *[no] {""}
+    }
+hangar-stats = Speed { $speed }   Turn rate { $turn_rate }   Radius { $radius }   Health { $health }
+hangar-weapon = Weapon: { $count } x { $damage } damage every { $cooldown }s{ $flak ->
+        [0] {""}
+       *[other] , bursting into { $flak } fragments
+    }
+hangar-tags = Tags: { $tags }
+
+stats-lifetime = Lifetime
+stats-achievements = Achievements
+stats-runs-played = Runs played: { $count }
+stats-best-score = Best score: { $score }
+stats-longest-survival = Longest survival: { $duration }
+stats-total-play-time = Total play time: { $duration }
+stats-shots-fired = Shots fired: { $count }
+stats-hits = Hits: { $count } ({ $accuracy }% accuracy)
+stats-damage-taken = Damage taken: { $amount }
+stats-asteroids-destroyed = Asteroids destroyed: { $count }
+stats-asteroids-of-tier = { $tier }: { $count }
+
+tier-small = Small
+tier-medium = Medium
+tier-large = Large
+
+## Settings
+
+settings-general = General
+settings-controls = Controls
+settings-accessibility = Accessibility
+settings-press-key = Press a key...
+
+setting-language = Language
+setting-window-mode = Window mode
+setting-resolution = Resolution
+setting-vsync = VSync
+setting-master-volume = Master volume
+setting-difficulty = Difficulty
+setting-hud-scale = HUD scale
+setting-camera-mode = Camera
+setting-background-density = Star density
+setting-radar-range = Radar range
+setting-radar-scale = Radar size
+setting-ui-scale = UI scale
+setting-color-palette = Colors
+setting-reduced-motion = Reduced motion
+setting-fire-mode = Fire
+setting-game-speed = Game speed
+
+action-forward = Forward
+action-backward = Backward
+action-turn-left = Turn left
+action-turn-right = Turn right
+action-roll-left = Roll left
+action-roll-right = Roll right
+action-fire = Fire
+
+value-on = On
+value-off = Off
+window-mode-windowed = Windowed
+window-mode-borderless = Borderless
+window-mode-fullscreen = Fullscreen
+difficulty-easy = Easy
+difficulty-normal = Normal
+difficulty-hard = Hard
+camera-mode-fixed = Fixed
+camera-mode-follow = Follow
+camera-mode-look-ahead = Look ahead
+palette-default = Default
+palette-red-green = Red-green safe
+palette-blue-yellow = Blue-yellow safe
+fire-mode-hold = Hold
+fire-mode-toggle = Toggle
+
+## HUD
+
+hud-hp = HP: { $hp }
+hud-score = Score: { $score }
+hud-combo = x{ $multiplier } ({ $seconds }s)
+hud-achievement-unlocked = Achievement unlocked: { $name }
+hud-boss = { $name } - phase { $phase }/{ $phases }
+
+popup-points = +{ $points }
+popup-long-shot = +{ $points } long shot
+popup-no-damage-streak = +{ $points } no damage streak
+
+## Achievements, keys listed in game.achievements.ron
+
+achievement-first-blood = First Blood
+achievement-first-blood-description = Destroy your first asteroid
+achievement-rock-breaker = Rock Breaker
+achievement-rock-breaker-description = Destroy 500 asteroids
+achievement-giant-slayer = Giant Slayer
+achievement-giant-slayer-description = Destroy 50 large asteroids
+achievement-trigger-happy = Trigger Happy
+achievement-trigger-happy-description = Fire 10000 missiles
+achievement-marksman = Marksman
+achievement-marksman-description = Keep 60% accuracy over at least 500 shots
+achievement-survivor = Survivor
+achievement-survivor-description = Survive for 3 minutes in a single run
+achievement-punching-bag = Punching Bag
+achievement-punching-bag-description = Take 10000 damage in total
+achievement-regular = Regular
+achievement-regular-description = Play 25 runs
+achievement-veteran = Veteran
+achievement-veteran-description = Spend an hour in space
+achievement-high-roller = High Roller
+achievement-high-roller-description = Score 25000 points in a single run
+
+## Bosses, keys listed in game.bosses.ron
+
+boss-core-asteroid = Core Asteroid
+boss-mothership = Mothership
@@ -0,0 +1,140 @@
+language-name = Русский
+digit-group-separator = {"\u00A0"}
+decimal-separator = {","}
+
+## Loading screen
+
+loading-title = Загрузка
+loading-failed = Не удалось загрузить ресурсы игры
+loading-failed-hint = Проверьте, что файлы есть в папке assets, и перезапустите игру.
+
+## Menus
+
+menu-title = Главное меню
+menu-play = Играть
+menu-hangar = Ангар
+menu-stats = Статистика
+menu-settings = Настройки
+menu-quit = Выход
+menu-back = Назад
+
+hangar-select = Выбрать
+hangar-hull = { $name }{ $equipped ->
+        [yes] {" "}(выбран)
+       *[no] {""}
+    }
+hangar-stats = Скорость { $speed }   Поворот { $turn_rate }   Радиус { $radius }   Прочность { $health }
+hangar-weapon = Оружие: { $count } x { $damage } урона каждые { $cooldown } с{ $flak ->
+        [0] {""}
+       *[other] , разлетается на { $flak } { $flak ->
+            [one] осколок
+            [few] осколка
+           *[many] осколков
+        }
+    }
+hangar-tags = Метки: { $tags }
+
+stats-lifetime = За всё время
+stats-achievements = Достижения
+stats-runs-played = Сыграно забегов: { $count }
+stats-best-score = Лучший счёт: { $score }
+stats-longest-survival = Дольше всего продержались: { $duration }
+stats-total-play-time = Всего в игре: { $duration }
+stats-shots-fired = Выстрелов: { $count }
+stats-hits = Попаданий: { $count } (точность { $accuracy }%)
+stats-damage-taken = Получено урона: { $amount }
+stats-asteroids-destroyed = Уничтожено астероидов: { $count }
+stats-asteroids-of-tier = { $tier }: { $count }
+
+tier-small = Малые
+tier-medium = Средние
+tier-large = Крупные
+
+## Settings
+
+settings-general = Общие
+settings-controls = Управление
+settings-accessibility = Доступность
+settings-press-key = Нажмите клавишу...
+
+setting-language = Язык
+setting-window-mode = Режим окна
+setting-resolution = Разрешение
+setting-vsync = Верт. синхронизация
+setting-master-volume = Общая громкость
+setting-difficulty = Сложность
+setting-hud-scale = Масштаб интерфейса
+setting-camera-mode = Камера
+setting-background-density = Плотность звёзд
+setting-radar-range = Дальность радара
+setting-radar-scale = Размер радара
+setting-ui-scale = Масштаб меню
+setting-color-palette = Цвета
+setting-reduced-motion = Меньше движения
+setting-fire-mode = Стрельба
+setting-game-speed = Скорость игры
+
+action-forward = Вперёд
+action-backward = Назад
+action-turn-left = Поворот влево
+action-turn-right = Поворот вправо
+action-roll-left = Крен влево
+action-roll-right = Крен вправо
+action-fire = Огонь
+
+value-on = Вкл
+value-off = Выкл
+window-mode-windowed = В окне
+window-mode-borderless = Без рамки
+window-mode-fullscreen = Полный экран
+difficulty-easy = Лёгкая
+difficulty-normal = Обычная
+difficulty-hard = Сложная
+camera-mode-fixed = Неподвижная
+camera-mode-follow = Следящая
+camera-mode-look-ahead = С упреждением
+palette-default = Обычные
+palette-red-green = Для красно-зелёного
+palette-blue-yellow = Для сине-жёлтого
+fire-mode-hold = Удержание
+fire-mode-toggle = Переключение
+
+## HUD
+
+hud-hp = Прочность: { $hp }
+hud-score = Счёт: { $score }
+hud-combo = x{ $multiplier } ({ $seconds } с)
+hud-achievement-unlocked = Получено достижение: { $name }
+hud-boss = { $name } - фаза { $phase }/{ $phases }
+
+popup-points = +{ $points }
+popup-long-shot = +{ $points } дальний выстрел
+popup-no-damage-streak = +{ $points } серия без урона
+
+## Achievements, keys listed in game.achievements.ron
+
+achievement-first-blood = Первая кровь
+achievement-first-blood-description = Уничтожьте первый астероид
+achievement-rock-breaker = Камнелом
+achievement-rock-breaker-description = Уничтожьте 500 астероидов
+achievement-giant-slayer = Победитель гигантов
+achievement-giant-slayer-description = Уничтожьте 50 больших астероидов
+achievement-trigger-happy = Скорый на спуск
+achievement-trigger-happy-description = Выпустите 10000 ракет
+achievement-marksman = Меткий стрелок
+achievement-marksman-description = Держите точность 60% минимум на 500 выстрелах
+achievement-survivor = Выживший
+achievement-survivor-description = Продержитесь 3 минуты в одном забеге
+achievement-punching-bag = Боксёрская груша
+achievement-punching-bag-description = Получите 10000 урона в сумме
+achievement-regular = Завсегдатай
+achievement-regular-description = Сыграйте 25 забегов
+achievement-veteran = Ветеран
+achievement-veteran-description = Проведите час в космосе
+achievement-high-roller = Крупная игра
+achievement-high-roller-description = Наберите 25000 очков в одном забеге
+
+## Bosses, keys listed in game.bosses.ron
+
+boss-core-asteroid = Астероид-ядро
+boss-mothership = Корабль-матка
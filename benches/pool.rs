@@ -0,0 +1,109 @@
+//! Compares spawning and despawning missile-like entities every frame against recycling them through
+//! the entity pool. Run with `cargo bench --bench pool`.
+
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use bevy_rapier3d::prelude::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+use tds_proto::pool::{EntityPool, Idle, PoolKind, Pooled};
+
+// Roughly what a glTF scene instance of a missile model adds below the gameplay entity
+const CHILDREN_PER_ENTITY: usize = 6;
+const FRAMES: usize = 120;
+
+
+#[derive(Resource, Debug)]
+struct Batch(usize);
+
+
+#[derive(Component, Debug)]
+struct Missile;
+
+
+fn spawn_missile_body(commands: &mut EntityCommands) {
+    commands.insert((
+        SpatialBundle::default(),
+        RigidBody::Dynamic,
+        Collider::ball(0.2),
+        Velocity::linear(Vec3::Z),
+        Missile,
+    )).with_children(|parent| {
+        for _ in 0..CHILDREN_PER_ENTITY {
+            parent.spawn(SpatialBundle::default());
+        }
+    });
+}
+
+
+fn churn(mut commands: Commands, query: Query<Entity, With<Missile>>, batch: Res<Batch>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for _ in 0..batch.0 {
+        spawn_missile_body(&mut commands.spawn_empty());
+    }
+}
+
+
+fn recycle(
+    mut commands: Commands,
+    query: Query<(Entity, &Pooled), (With<Missile>, Without<Idle>)>,
+    mut pool: ResMut<EntityPool>,
+    batch: Res<Batch>,
+) {
+    for (entity, &Pooled(kind)) in query.iter() {
+        pool.release(&mut commands, entity, kind);
+    }
+    for _ in 0..batch.0 {
+        let missile = pool.acquire(&mut commands, PoolKind::Missile);
+        let mut missile_commands = commands.entity(missile.entity);
+        if missile.recycled {
+            missile_commands.insert((Transform::default(), Velocity::linear(Vec3::Z)));
+        } else {
+            spawn_missile_body(&mut missile_commands);
+        }
+    }
+}
+
+
+fn app(batch: usize, pooled: bool) -> App {
+    let mut app = App::new();
+    app
+        .add_plugins((MinimalPlugins, TransformPlugin, HierarchyPlugin, AssetPlugin::default()))
+        .init_asset::<Mesh>()
+        .init_asset::<Scene>()
+        .add_plugins(RapierPhysicsPlugin::<NoUserData>::default())
+        .init_resource::<EntityPool>()
+        .insert_resource(Batch(batch));
+    if pooled {
+        app.add_systems(Update, recycle);
+    } else {
+        app.add_systems(Update, churn);
+    }
+    app
+}
+
+
+fn bench_pool(c: &mut Criterion) {
+    let mut group = c.benchmark_group("missiles");
+    for batch in [16, 64, 256] {
+        for (name, pooled) in [("spawn_despawn", false), ("pooled", true)] {
+            group.bench_with_input(BenchmarkId::new(name, batch), &batch, |b, &batch| {
+                b.iter_batched(
+                    || app(batch, pooled),
+                    |mut app| {
+                        for _ in 0..FRAMES {
+                            app.update();
+                        }
+                    },
+                    criterion::BatchSize::LargeInput,
+                );
+            });
+        }
+    }
+    group.finish();
+}
+
+
+criterion_group!(benches, bench_pool);
+criterion_main!(benches);
@@ -0,0 +1,84 @@
+use std::{collections::BTreeSet, env, fs, path::{Path, PathBuf}};
+
+const LOCALES_DIR: &str = "assets/locales";
+const REFERENCE_LOCALE: &str = "en-US";
+const MESSAGES_FILE: &str = "main.ftl";
+// Game data naming messages in `*_key` fields
+const DATA_FILES: &[&str] = &["assets/game.achievements.ron", "assets/game.bosses.ron"];
+
+
+// Message and term identifiers defined at the start of a line, e.g. `menu-play = Play`
+fn message_keys(source: &str) -> BTreeSet<String> {
+    source.lines()
+        .filter(|line| !line.starts_with(|c: char| c.is_whitespace() || c == '#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, _)| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+        .collect()
+}
+
+
+// Values of `*_key: "..."` fields, e.g. `name_key: "boss-mothership"`
+fn data_keys(source: &str) -> BTreeSet<String> {
+    source.lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("//"))
+        .filter_map(|line| line.split_once("_key:"))
+        .filter_map(|(_, value)| value.trim().strip_prefix('"')?.split_once('"'))
+        .map(|(key, _)| key.to_string())
+        .collect()
+}
+
+
+fn main() {
+    println!("cargo:rerun-if-changed={}", LOCALES_DIR);
+
+    let mut locales: Vec<(String, PathBuf)> = fs::read_dir(LOCALES_DIR)
+        .expect("assets/locales should exist")
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().join(MESSAGES_FILE))
+        .filter(|path| path.is_file())
+        .map(|path| {
+            let language = path.parent().and_then(Path::file_name).unwrap().to_string_lossy().into_owned();
+            println!("cargo:rerun-if-changed={}", path.display());
+            (language, path)
+        })
+        .collect();
+    locales.sort();
+
+    // Every locale has to define exactly the messages of the reference one
+    let keys_of = |path: &Path| message_keys(&fs::read_to_string(path).expect("locale file should be readable"));
+    let (_, reference_path) = locales.iter().find(|(language, _)| language == REFERENCE_LOCALE)
+        .expect("the reference locale should exist");
+    let reference = keys_of(reference_path);
+    let mut problems = Vec::new();
+    for (language, path) in locales.iter() {
+        let keys = keys_of(path);
+        for missing in reference.difference(&keys) {
+            problems.push(format!("{}: missing `{}`", language, missing));
+        }
+        for extra in keys.difference(&reference) {
+            problems.push(format!("{}: `{}` is not in {}", language, extra, REFERENCE_LOCALE));
+        }
+    }
+    // Messages named by the game data have to exist as well
+    for data_file in DATA_FILES {
+        println!("cargo:rerun-if-changed={}", data_file);
+        let source = fs::read_to_string(data_file).expect("game data file should be readable");
+        for key in data_keys(&source).difference(&reference) {
+            problems.push(format!("{}: `{}` is not in {}", data_file, key, REFERENCE_LOCALE));
+        }
+    }
+    if !problems.is_empty() {
+        panic!("Locales are out of sync:\n{}", problems.join("\n"));
+    }
+
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let mut generated = String::from("/// Language identifier and Fluent source of every bundled locale\npub const LOCALES: &[(&str, &str)] = &[\n");
+    for (language, path) in locales.iter() {
+        generated.push_str(&format!("    ({:?}, include_str!({:?})),\n", language, manifest_dir.join(path)));
+    }
+    generated.push_str("];\n");
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out_dir.join("locales.rs"), generated).expect("generated locale table should be writable");
+}
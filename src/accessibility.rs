@@ -0,0 +1,231 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+const UI_SCALES: &'static [f32] = &[1., 1.25, 1.5, 2.];
+const GAME_SPEEDS: &'static [f32] = &[0.5, 0.75, 1.];
+
+
+#[derive(Serialize, Deserialize, Default, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ColorPalette {
+    #[default]
+    Default,
+    /// Avoids telling colors apart by red against green (protanopia, deuteranopia)
+    RedGreen,
+    /// Avoids telling colors apart by blue against yellow (tritanopia)
+    BlueYellow,
+}
+
+
+impl ColorPalette {
+    fn next(self) -> Self {
+        match self {
+            Self::Default => Self::RedGreen,
+            Self::RedGreen => Self::BlueYellow,
+            Self::BlueYellow => Self::Default,
+        }
+    }
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Default => "palette-default",
+            Self::RedGreen => "palette-red-green",
+            Self::BlueYellow => "palette-blue-yellow",
+        }
+    }
+
+    pub fn colors(self) -> Palette {
+        match self {
+            Self::Default => Palette {
+                ship: Color::rgb(0.3, 1., 0.4),
+                asteroid: Color::rgb(0.8, 0.7, 0.5),
+                enemy: Color::rgb(1., 0.3, 0.3),
+                pickup: Color::rgb(0.4, 0.8, 1.),
+                threat_critical: Color::rgb(1., 0.2, 0.2),
+                threat_high: Color::rgb(1., 0.6, 0.1),
+                threat_low: Color::rgb(1., 0.95, 0.4),
+                popup: Color::rgb(1., 0.85, 0.3),
+                bonus_popup: Color::rgb(0.4, 0.9, 1.),
+            },
+            // Blue against orange, told apart by brightness as well
+            Self::RedGreen => Palette {
+                ship: Color::rgb(0.35, 0.7, 1.),
+                asteroid: Color::rgb(0.75, 0.75, 0.75),
+                enemy: Color::rgb(1., 0.5, 0.),
+                pickup: Color::rgb(0.95, 0.95, 0.3),
+                threat_critical: Color::rgb(1., 0.45, 0.),
+                threat_high: Color::rgb(1., 0.8, 0.35),
+                threat_low: Color::rgb(0.6, 0.8, 1.),
+                popup: Color::rgb(1., 0.85, 0.3),
+                bonus_popup: Color::rgb(0.35, 0.7, 1.),
+            },
+            // Red against cyan
+            Self::BlueYellow => Palette {
+                ship: Color::rgb(0.2, 0.9, 0.9),
+                asteroid: Color::rgb(0.75, 0.75, 0.75),
+                enemy: Color::rgb(1., 0.2, 0.3),
+                pickup: Color::rgb(0.2, 0.9, 0.9),
+                threat_critical: Color::rgb(1., 0.1, 0.2),
+                threat_high: Color::rgb(1., 0.5, 0.6),
+                threat_low: Color::rgb(0.9, 0.9, 0.9),
+                popup: Color::rgb(1., 0.6, 0.7),
+                bonus_popup: Color::rgb(0.2, 0.9, 0.9),
+            },
+        }
+    }
+}
+
+
+/// Colors of the HUD and effects that carry meaning
+#[derive(Clone, Copy, Debug)]
+pub struct Palette {
+    pub ship: Color,
+    pub asteroid: Color,
+    pub enemy: Color,
+    pub pickup: Color,
+    pub threat_critical: Color,
+    pub threat_high: Color,
+    pub threat_low: Color,
+    pub popup: Color,
+    pub bonus_popup: Color,
+}
+
+
+#[derive(Serialize, Deserialize, Default, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum FireMode {
+    /// Fires while the fire key is held
+    #[default]
+    Hold,
+    /// A press starts firing, the next one stops it
+    Toggle,
+}
+
+
+impl FireMode {
+    fn next(self) -> Self {
+        match self {
+            Self::Hold => Self::Toggle,
+            Self::Toggle => Self::Hold,
+        }
+    }
+
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Hold => "fire-mode-hold",
+            Self::Toggle => "fire-mode-toggle",
+        }
+    }
+}
+
+
+/// Accessibility options, stored with the settings and read by the UI and effects systems
+#[derive(Resource, Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct Accessibility {
+    /// Multiplier of every UI size, text included
+    pub ui_scale: f32,
+    pub palette: ColorPalette,
+    /// Turns off screen shake, flashes and other motion that isn't needed to play
+    pub reduced_motion: bool,
+    pub fire_mode: FireMode,
+    /// Speed of the game world relative to real time
+    pub game_speed: f32,
+}
+
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            ui_scale: 1.,
+            palette: ColorPalette::default(),
+            reduced_motion: false,
+            fire_mode: FireMode::default(),
+            game_speed: 1.,
+        }
+    }
+}
+
+
+impl Accessibility {
+    pub fn colors(&self) -> Palette {
+        self.palette.colors()
+    }
+
+    pub fn next_ui_scale(&mut self) {
+        self.ui_scale = next_in(UI_SCALES, self.ui_scale);
+    }
+
+    pub fn next_palette(&mut self) {
+        self.palette = self.palette.next();
+    }
+
+    pub fn next_fire_mode(&mut self) {
+        self.fire_mode = self.fire_mode.next();
+    }
+
+    pub fn next_game_speed(&mut self) {
+        self.game_speed = next_in(GAME_SPEEDS, self.game_speed);
+    }
+
+    /// Moves hand-edited values to the nearest option, neither the UI nor the clock takes just any number
+    pub fn snap_to_options(&mut self) {
+        self.ui_scale = nearest_in(UI_SCALES, self.ui_scale);
+        self.game_speed = nearest_in(GAME_SPEEDS, self.game_speed);
+    }
+}
+
+
+fn next_in(values: &[f32], current: f32) -> f32 {
+    let index = values.iter().position(|&value| value == current).map_or(0, |i| i + 1);
+    values[index % values.len()]
+}
+
+
+// Both option lists hold 1, the default for anything that isn't a number
+fn nearest_in(values: &[f32], current: f32) -> f32 {
+    if !current.is_finite() {
+        return 1.;
+    }
+    values.iter().copied()
+        .min_by(|a, b| (a - current).abs().total_cmp(&(b - current).abs()))
+        .unwrap_or(1.)
+}
+
+
+pub struct AccessibilityPlugin;
+
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Accessibility>()
+            .add_systems(PreUpdate, (
+                sync_accessibility.run_if(resource_changed::<Settings>),
+                apply_accessibility.run_if(resource_changed::<Accessibility>),
+            ).chain());
+    }
+}
+
+
+fn sync_accessibility(settings: Res<Settings>, mut accessibility: ResMut<Accessibility>) {
+    accessibility.set_if_neq(settings.accessibility.clone());
+}
+
+
+fn apply_accessibility(
+    accessibility: Res<Accessibility>,
+    mut ui_scale: ResMut<UiScale>,
+    mut time: ResMut<Time<Virtual>>,
+    mut applied_game_speed: Local<Option<f32>>,
+) {
+    // Bevy scales every `Val::Px` of the UI, so font sizes and button dimensions follow
+    if ui_scale.0 != accessibility.ui_scale {
+        ui_scale.0 = accessibility.ui_scale;
+    }
+    // Only a new game speed touches the clock, so other options don't undo the console's `timescale`
+    if *applied_game_speed != Some(accessibility.game_speed) {
+        *applied_game_speed = Some(accessibility.game_speed);
+        time.set_relative_speed(accessibility.game_speed);
+    }
+}
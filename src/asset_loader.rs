@@ -1,43 +1,252 @@
-use bevy::prelude::*;
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::{BoxedFuture, HashSet},
+};
+use bevy_rapier3d::prelude::*;
 use rand::seq::IteratorRandom;
+use serde::Deserialize;
+
+use crate::state::GameState;
+use crate::loading::check_loading_progress;
+use crate::spaceship::HullStats;
 
-const SPACESHIP_MODEL_PATHS: &'static [&'static str] = &[
-    "Spaceship-1.glb#Scene0",
-    "Spaceship-2.glb#Scene0",
-    "Spaceship-3.glb#Scene0",
-    "Spaceship-4.glb#Scene0",
-];
-const ASTEROID_MODEL_PATH: &'static [&'static str] = &[
-    "Planet-fractured-1.glb#Scene0",
-    "Planet-fractured-2.glb#Scene0"
-];
-const BULLET_MODEL_PATH: &'static [&'static str] = &[
-    "Bullet-1.glb#Scene0",
-];
+const MODEL_MANIFEST_PATH: &'static str = "models.manifest.ron";
+const DEFAULT_SCENE_LABEL: &'static str = "Scene0";
 
 const PLAY_ICON_PATH: &'static str = "Right.png";
 const EXIT_ICON_PATH: &'static str = "Exit.png";
 
 
+#[derive(Deserialize, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ModelKind {
+    Spaceship,
+    Asteroid,
+    Bullet,
+}
+
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+pub enum ColliderShape {
+    Ball(f32),
+    Capsule { half_height: f32, radius: f32 },
+    Cuboid(f32, f32, f32),
+    /// Convex hull of the model's meshes, a ball of the given radius until it is built
+    ConvexHull(f32),
+    /// Convex decomposition of the model's meshes, for concave models
+    ConvexDecomposition(f32),
+}
+
+
+impl ColliderShape {
+    /// Collider described by the manifest alone, mesh based shapes fall back to their ball
+    pub fn collider(&self) -> Collider {
+        match *self {
+            Self::Ball(radius) | Self::ConvexHull(radius) | Self::ConvexDecomposition(radius) => Collider::ball(radius),
+            Self::Capsule { half_height, radius } => Collider::capsule_z(half_height, radius),
+            Self::Cuboid(x, y, z) => Collider::cuboid(x, y, z),
+        }
+    }
+
+    /// Radius of the sphere enclosing the shape
+    pub fn radius(&self) -> f32 {
+        match *self {
+            Self::Ball(radius) | Self::ConvexHull(radius) | Self::ConvexDecomposition(radius) => radius,
+            Self::Capsule { half_height, radius } => half_height + radius,
+            Self::Cuboid(x, y, z) => Vec3::new(x, y, z).length(),
+        }
+    }
+
+    pub fn is_mesh_based(&self) -> bool {
+        matches!(self, Self::ConvexHull(_) | Self::ConvexDecomposition(_))
+    }
+
+    fn is_valid(&self) -> bool {
+        match *self {
+            Self::Ball(radius) | Self::ConvexHull(radius) | Self::ConvexDecomposition(radius) => radius > 0.,
+            Self::Capsule { half_height, radius } => half_height >= 0. && radius > 0.,
+            Self::Cuboid(x, y, z) => x > 0. && y > 0. && z > 0.,
+        }
+    }
+}
+
+
+fn default_scale() -> f32 { 1. }
+
+
+/// Single model entry of the manifest file
+#[derive(Deserialize, Clone, Debug)]
+pub struct ModelEntry {
+    pub name: String,
+    pub kind: ModelKind,
+    /// glTF path, the first scene is used when no `#Scene` label is given
+    pub path: String,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    pub collider: ColliderShape,
+    /// Yaw in degrees that turns the model's nose towards the entity's movement direction
+    #[serde(default)]
+    pub forward_correction: f32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Handling and loadout of spaceship models
+    #[serde(default)]
+    pub hull: Option<HullStats>,
+}
+
+
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct ModelManifest {
+    pub models: Vec<ModelEntry>,
+}
+
+
+impl ModelManifest {
+    /// Returns a human readable description for every bad entry
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let mut names = HashSet::new();
+        for (index, entry) in self.models.iter().enumerate() {
+            let name = if entry.name.is_empty() { format!("#{}", index) } else { entry.name.clone() };
+            if entry.name.is_empty() {
+                errors.push(format!("{}: model has no name", MODEL_MANIFEST_PATH));
+            } else if !names.insert(entry.name.as_str()) {
+                errors.push(format!("{}: duplicate model name '{}'", MODEL_MANIFEST_PATH, name));
+            }
+            if entry.path.is_empty() {
+                errors.push(format!("{}: model '{}' has no path", MODEL_MANIFEST_PATH, name));
+            }
+            if !entry.scale.is_finite() || entry.scale <= 0. {
+                errors.push(format!("{}: model '{}' has non-positive scale {}", MODEL_MANIFEST_PATH, name, entry.scale));
+            }
+            if !entry.collider.is_valid() {
+                errors.push(format!("{}: model '{}' has invalid collider {:?}", MODEL_MANIFEST_PATH, name, entry.collider));
+            }
+            if let Some(hull) = &entry.hull {
+                if entry.kind != ModelKind::Spaceship {
+                    errors.push(format!("{}: model '{}' has hull stats but is not a spaceship", MODEL_MANIFEST_PATH, name));
+                }
+                if !hull_stats_valid(hull) {
+                    errors.push(format!("{}: model '{}' has invalid hull stats", MODEL_MANIFEST_PATH, name));
+                }
+            }
+        }
+        for kind in [ModelKind::Spaceship, ModelKind::Asteroid, ModelKind::Bullet] {
+            if !self.models.iter().any(|entry| entry.kind == kind) {
+                errors.push(format!("{}: no {:?} models listed", MODEL_MANIFEST_PATH, kind));
+            }
+        }
+        errors
+    }
+}
+
+
+fn positive(value: f32) -> bool {
+    value.is_finite() && value > 0.
+}
+
+
+fn non_negative(value: f32) -> bool {
+    value.is_finite() && value >= 0.
+}
+
+
+fn hull_stats_valid(hull: &HullStats) -> bool {
+    let weapon = &hull.weapon;
+    positive(hull.speed) && positive(hull.turn_rate) && positive(hull.roll_rate) && positive(hull.health)
+        && non_negative(hull.collision_damage)
+        && positive(weapon.cooldown) && non_negative(weapon.damage) && positive(weapon.missile_speed)
+        && positive(weapon.lifetime) && non_negative(weapon.spread) && weapon.count > 0
+}
+
+
+/// Model from the manifest with its scene handle
+#[derive(Clone, Debug)]
+pub struct ModelAsset {
+    pub name: String,
+    pub scene: Handle<Scene>,
+    pub scale: f32,
+    pub collider_shape: ColliderShape,
+    /// Built from the scene's meshes once it has loaded, see `mesh_colliders`
+    pub mesh_collider: Option<Collider>,
+    pub forward_correction: Quat,
+    pub tags: Vec<String>,
+    pub hull: Option<HullStats>,
+}
+
+
+impl ModelAsset {
+    fn new(entry: &ModelEntry, asset_server: &AssetServer) -> Self {
+        let path = if entry.path.contains('#') {
+            entry.path.clone()
+        } else {
+            format!("{}#{}", entry.path, DEFAULT_SCENE_LABEL)
+        };
+        Self {
+            name: entry.name.clone(),
+            scene: asset_server.load(path),
+            scale: entry.scale,
+            collider_shape: entry.collider.clone(),
+            mesh_collider: None,
+            forward_correction: Quat::from_rotation_y(entry.forward_correction.to_radians()),
+            tags: entry.tags.clone(),
+            hull: entry.hull.clone(),
+        }
+    }
+
+    pub fn collider(&self) -> Collider {
+        self.mesh_collider.clone().unwrap_or_else(|| self.collider_shape.collider())
+    }
+
+    /// Transform of the gameplay entity carrying this model
+    pub fn transform(&self, transform: Transform) -> Transform {
+        transform.with_scale(Vec3::splat(self.scale))
+    }
+
+    /// The scene is spawned as a child, so the forward correction does not affect movement
+    pub fn scene_bundle(&self) -> SceneBundle {
+        SceneBundle {
+            scene: self.scene.clone(),
+            transform: Transform::from_rotation(self.forward_correction),
+            ..default()
+        }
+    }
+}
+
+
 #[derive(Resource, Default, Debug)]
 pub struct SceneAssets {
-    pub spaceship: Vec<Handle<Scene>>,
-    pub asteroid: Vec<Handle<Scene>>,
-    pub bullet: Vec<Handle<Scene>>,
+    pub manifest: Handle<ModelManifest>,
+    pub spaceship: Vec<ModelAsset>,
+    pub asteroid: Vec<ModelAsset>,
+    pub bullet: Vec<ModelAsset>,
 }
 
 
 impl SceneAssets {
-    pub fn get_random_spaceship(&self) -> Handle<Scene> {
-        self.spaceship.iter().choose(&mut rand::thread_rng()).unwrap().clone()
+    /// Spaceship with the given name, or the first one listed
+    pub fn get_spaceship(&self, name: Option<&str>) -> &ModelAsset {
+        name.and_then(|name| self.spaceship.iter().find(|model| model.name == name))
+            .unwrap_or(&self.spaceship[0])
+    }
+
+    pub fn get_random_asteroid(&self) -> &ModelAsset {
+        self.asteroid.iter().choose(&mut rand::thread_rng()).unwrap()
     }
 
-    pub fn get_random_asteroid(&self) -> Handle<Scene> {
-        self.asteroid.iter().choose(&mut rand::thread_rng()).unwrap().clone()
+    pub fn get_random_bullet(&self) -> &ModelAsset {
+        self.bullet.iter().choose(&mut rand::thread_rng()).unwrap()
     }
 
-    pub fn get_random_bullet(&self) -> Handle<Scene> {
-        self.bullet.iter().choose(&mut rand::thread_rng()).unwrap().clone()
+    /// Model of any kind with the given name
+    pub fn get(&self, name: &str) -> Option<&ModelAsset> {
+        self.spaceship.iter().chain(self.asteroid.iter()).chain(self.bullet.iter()).find(|model| model.name == name)
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut ModelAsset> {
+        self.spaceship.iter_mut().chain(self.asteroid.iter_mut()).chain(self.bullet.iter_mut())
     }
 }
 
@@ -49,6 +258,66 @@ pub struct ImageAssets {
 }
 
 
+/// Every handle that has to finish loading before leaving `GameState::Loading`
+#[derive(Resource, Default, Debug)]
+pub struct LoadingAssets {
+    pub handles: Vec<UntypedHandle>,
+    /// Problems found in loaded data, reported next to the failed assets
+    pub errors: Vec<String>,
+}
+
+
+impl LoadingAssets {
+    pub fn track<A: Asset>(&mut self, handle: &Handle<A>) {
+        self.handles.push(handle.clone().untyped());
+    }
+}
+
+
+/// Loads any deserializable asset from a RON data file
+pub struct RonAssetLoader<A> {
+    extensions: Vec<&'static str>,
+    _marker: PhantomData<fn() -> A>,
+}
+
+
+impl<A> RonAssetLoader<A> {
+    pub fn new(extensions: &[&'static str]) -> Self {
+        Self {
+            extensions: extensions.to_vec(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+
+impl<A> AssetLoader for RonAssetLoader<A>
+where
+    A: Asset + for<'de> Deserialize<'de>,
+{
+    type Asset = A;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<A, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<A>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+}
+
+
 pub struct AssetLoaderPlugin;
 
 
@@ -57,26 +326,70 @@ impl Plugin for AssetLoaderPlugin {
         app
             .init_resource::<SceneAssets>()
             .init_resource::<ImageAssets>()
+            .init_resource::<LoadingAssets>()
+            .init_asset::<ModelManifest>()
+            .register_asset_loader(RonAssetLoader::<ModelManifest>::new(&["manifest.ron"]))
             .add_systems(Startup, (
-                load_scene_assets,
+                load_model_manifest,
                 load_image_assets,
-            ));
+            ))
+            .add_systems(Update, load_scene_assets
+                .before(check_loading_progress)
+                .run_if(in_state(GameState::Loading)));
     }
 }
 
 
-fn load_scene_assets(mut scene_assets: ResMut<SceneAssets>, asset_server: Res<AssetServer>) {
-    *scene_assets = SceneAssets {
-        spaceship: SPACESHIP_MODEL_PATHS.iter().map(|path| asset_server.load(path.to_string())).collect(),
-        asteroid: ASTEROID_MODEL_PATH.iter().map(|path| asset_server.load(path.to_string())).collect(),
-        bullet: BULLET_MODEL_PATH.iter().map(|path| asset_server.load(path.to_string())).collect(),
-    };
+fn load_model_manifest(
+    mut scene_assets: ResMut<SceneAssets>,
+    mut loading_assets: ResMut<LoadingAssets>,
+    asset_server: Res<AssetServer>,
+) {
+    scene_assets.manifest = asset_server.load(MODEL_MANIFEST_PATH);
+    loading_assets.track(&scene_assets.manifest);
+}
+
+
+// Queues every model of the manifest as soon as the manifest itself is in
+fn load_scene_assets(
+    mut scene_assets: ResMut<SceneAssets>,
+    mut loading_assets: ResMut<LoadingAssets>,
+    asset_server: Res<AssetServer>,
+    manifests: Res<Assets<ModelManifest>>,
+    mut queued: Local<bool>,
+) {
+    if *queued {
+        return;
+    }
+    let Some(manifest) = manifests.get(&scene_assets.manifest) else { return };
+    *queued = true;
+
+    let errors = manifest.validate();
+    if !errors.is_empty() {
+        loading_assets.errors.extend(errors);
+        return;
+    }
+    for entry in manifest.models.iter() {
+        let model = ModelAsset::new(entry, &asset_server);
+        loading_assets.track(&model.scene);
+        match entry.kind {
+            ModelKind::Spaceship => scene_assets.spaceship.push(model),
+            ModelKind::Asteroid => scene_assets.asteroid.push(model),
+            ModelKind::Bullet => scene_assets.bullet.push(model),
+        }
+    }
 }
 
 
-fn load_image_assets(mut image_assets: ResMut<ImageAssets>, asset_server: Res<AssetServer>) {
+fn load_image_assets(
+    mut image_assets: ResMut<ImageAssets>,
+    mut loading_assets: ResMut<LoadingAssets>,
+    asset_server: Res<AssetServer>,
+) {
     *image_assets = ImageAssets {
         play_icon: asset_server.load(PLAY_ICON_PATH),
         exit_icon: asset_server.load(EXIT_ICON_PATH),
     };
+    loading_assets.track(&image_assets.play_icon);
+    loading_assets.track(&image_assets.exit_icon);
 }
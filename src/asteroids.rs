@@ -1,32 +1,217 @@
-use std::ops::Range;
+use std::{ops::Range, time::Duration};
 
-use bevy::prelude::*;
+use bevy::{prelude::*, utils::HashMap};
 use bevy_rapier3d::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
 
-use crate::asset_loader::SceneAssets;
+use crate::asset_loader::{LoadingAssets, ModelAsset, RonAssetLoader, SceneAssets};
 use crate::schedule::InGameSet;
-use crate::hud::GameScoreChangeEvent;
+use crate::scoring::{ScoreValue, TargetDestroyedEvent};
 use crate::movement::{DirVector, MovingObjectBundle};
-use crate::health::Health;
-use crate::collision_detection::CollisionDamage;
-use crate::despawn_routine::DisposableEntity;
+use crate::interpolation::InterpolatedTransform;
+use crate::health::{Health, Invulnerable};
+use crate::collision_detection::{apply_collision_damage, CollisionDamage, CollisionDamageEvent, MissileArmor};
+use crate::despawn_routine::{DespawnPolicy, DisposableEntity};
+use crate::settings::Settings;
+use crate::camera::CameraShakeEvent;
+use crate::particles::{ParticleBurstEvent, ParticleEffectKind};
+use crate::mesh_colliders::MeshColliderCache;
+use crate::pool::{EntityPool, Idle, PoolKind};
+use crate::lifetime::{Lifetime, LifetimeFade};
+use crate::stats::StatEvent;
+use crate::hud::RadarBlip;
+use crate::state::GameState;
+use crate::boss::Boss;
+use crate::spaceship::{Chilled, Spaceship};
+use crate::console::{parse_arg, ConsoleArgs, ConsoleCommandsExt, ConsoleLog};
 
 const VELOCITY_SCALAR: f32 = 10.;
 
 const SPAWN_RANGE_X: Range<f32> = -25.0..25.0;
 const SPAWN_RANGE_Z: Range<f32> = 0.0..25.0;
 const SPAWN_SECONDS: f32 = 1.;
+const WAVE_SECONDS: f32 = 30.;
+// Extra spawn rate of every wave past the first
+const WAVE_SPAWN_RATE_STEP: f32 = 0.15;
+const MAX_CONSOLE_SPAWN: u32 = 100;
+const VARIANTS_PATH: &'static str = "game.asteroids.ron";
 
 const ROTATION_SPEED: f32 = 2.5;
-const RADIUS: f32 = 2.5;
+const DEBRIS_RADIUS: f32 = 0.25;
 const HEALTH: f32 = 80.;
+const POINTS: u32 = 100;
 const COLLISION_DAMAGE: f32 = 35.;
+const EXPLOSION_TRAUMA: f32 = 0.3;
+const DEBRIS_LIFETIME: f32 = 3.;
+const DEBRIS_FADE_FRACTION: f32 = 0.4;
+
+const BLAST_TRAUMA: f32 = 0.6;
+const SHARD_RADIUS: f32 = 0.5;
+const SHARD_HEALTH: f32 = 1.;
+const SHARD_LIFETIME: f32 = 8.;
+const SHARD_SHRINK_FRACTION: f32 = 0.2;
+const SHARD_COLOR: Color = Color::rgb(0.65, 1., 0.4);
 
 
 #[derive(Component, Debug)]
 pub struct Asteroid;
 
 
+#[derive(Component, Serialize, Deserialize, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum AsteroidTier {
+    Small,
+    Medium,
+    Large,
+}
+
+
+impl AsteroidTier {
+    pub const ALL: [AsteroidTier; 3] = [AsteroidTier::Small, AsteroidTier::Medium, AsteroidTier::Large];
+
+    // Medium asteroids are the most common
+    fn random() -> Self {
+        match rand::thread_rng().gen_range(0..10) {
+            0..=2 => Self::Small,
+            3..=7 => Self::Medium,
+            _ => Self::Large,
+        }
+    }
+
+    fn scale(self) -> f32 {
+        match self {
+            Self::Small => 0.6,
+            Self::Medium => 1.,
+            Self::Large => 1.5,
+        }
+    }
+
+    fn health(self) -> f32 {
+        HEALTH * self.scale() * self.scale()
+    }
+
+    /// Message key of the tier's name
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Small => "tier-small",
+            Self::Medium => "tier-medium",
+            Self::Large => "tier-large",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|tier| format!("{:?}", tier).eq_ignore_ascii_case(name))
+    }
+
+    fn points(self) -> u32 {
+        match self {
+            Self::Small => POINTS / 2,
+            Self::Medium => POINTS,
+            Self::Large => POINTS * 2,
+        }
+    }
+}
+
+
+/// Behavior of an asteroid variant, on top of what every asteroid does
+#[derive(Component, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+pub enum AsteroidKind {
+    #[default]
+    Plain,
+    /// Damages everything within `radius` on death, less the farther away, setting off other explosive asteroids
+    Explosive { radius: f32, damage: f32 },
+    /// Takes the `missile_damage` fraction of the damage dealt by missiles
+    Metallic { missile_damage: f32 },
+    /// Cuts the ship's speed down to the `slow` fraction for a while on contact
+    Ice { slow: f32, seconds: f32 },
+    /// Releases shards on death, which home in on the ship
+    Swarmer { shards: u32, speed: f32, turn_rate: f32, damage: f32 },
+}
+
+
+impl AsteroidKind {
+    pub const NAMES: [&'static str; 5] = ["plain", "explosive", "metallic", "ice", "swarmer"];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Plain => Self::NAMES[0],
+            Self::Explosive { .. } => Self::NAMES[1],
+            Self::Metallic { .. } => Self::NAMES[2],
+            Self::Ice { .. } => Self::NAMES[3],
+            Self::Swarmer { .. } => Self::NAMES[4],
+        }
+    }
+}
+
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct AsteroidVariant {
+    pub kind: AsteroidKind,
+    pub weight: u32,
+    pub first_wave: u32,
+    pub tint: Option<(f32, f32, f32)>,
+    /// Multiplier of the tier's score
+    pub points: f32,
+    /// Multiplier of the tier's health
+    pub health: f32,
+}
+
+
+impl Default for AsteroidVariant {
+    fn default() -> Self {
+        Self {
+            kind: AsteroidKind::Plain,
+            weight: 1,
+            first_wave: 1,
+            tint: None,
+            points: 1.,
+            health: 1.,
+        }
+    }
+}
+
+
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct AsteroidVariants {
+    pub variants: Vec<AsteroidVariant>,
+}
+
+
+impl AsteroidVariants {
+    fn random(&self, wave: u32) -> Option<&AsteroidVariant> {
+        let available: Vec<&AsteroidVariant> = self.variants.iter()
+            .filter(|variant| variant.first_wave <= wave)
+            .collect();
+        available.choose_weighted(&mut rand::thread_rng(), |variant| variant.weight).ok().copied()
+    }
+
+    fn find(&self, name: &str) -> Option<&AsteroidVariant> {
+        self.variants.iter().find(|variant| variant.kind.name().eq_ignore_ascii_case(name))
+    }
+}
+
+
+#[derive(Resource, Default, Debug)]
+pub struct AsteroidAssets {
+    pub variants: Handle<AsteroidVariants>,
+    pub shard_mesh: Handle<Mesh>,
+    pub shard_material: Handle<StandardMaterial>,
+}
+
+
+/// Color multiplied into the materials of the asteroid's model once its scene is spawned
+#[derive(Component, Clone, Copy, Debug)]
+pub struct AsteroidTint(pub Color);
+
+
+#[derive(Component, Debug)]
+pub struct SwarmerShard {
+    speed: f32,
+    turn_rate: f32,
+}
+
+
 #[derive(Component, Debug)]
 pub struct AsteroidParticle;
 
@@ -46,6 +231,41 @@ impl SpawnTimer {
 }
 
 
+// Kept apart from `AsteroidWave`, which only changes when a new wave starts
+#[derive(Resource, Debug)]
+struct WaveTimer {
+    timer: Timer,
+}
+
+
+impl Default for WaveTimer {
+    fn default() -> Self {
+        Self { timer: Timer::from_seconds(WAVE_SECONDS, TimerMode::Repeating) }
+    }
+}
+
+
+/// Waves advance over time, each one spawning asteroids faster
+#[derive(Resource, Debug)]
+pub struct AsteroidWave {
+    pub number: u32,
+}
+
+
+impl Default for AsteroidWave {
+    fn default() -> Self {
+        Self { number: 1 }
+    }
+}
+
+
+impl AsteroidWave {
+    fn spawn_rate(&self) -> f32 {
+        1. + WAVE_SPAWN_RATE_STEP * (self.number - 1) as f32
+    }
+}
+
+
 pub struct AsteroidPlugin;
 
 
@@ -53,86 +273,424 @@ impl Plugin for AsteroidPlugin {
     fn build(&self, app: &mut App) {
         app
             .insert_resource(SpawnTimer::new(SPAWN_SECONDS))
-            .add_systems(Update, (
-                spawn_asteroid,
+            .init_resource::<AsteroidWave>()
+            .init_resource::<WaveTimer>()
+            .init_resource::<AsteroidAssets>()
+            .init_asset::<AsteroidVariants>()
+            .register_asset_loader(RonAssetLoader::<AsteroidVariants>::new(&["asteroids.ron"]))
+            .add_console_command(
+                "spawn",
+                "spawn asteroid <n> [tier] [variant] - spawns asteroids of a random or given tier and variant",
+                &["asteroid"],
+                spawn_command,
+            )
+            .add_console_command("wave", "wave <n> - jumps to the given wave", &[], wave_command)
+            .add_systems(Update, apply_difficulty.run_if(resource_changed::<Settings>.or_else(resource_changed::<AsteroidWave>)))
+            .add_systems(Startup, load_asteroid_assets)
+            .add_systems(OnEnter(GameState::GameOver), reset_wave)
+            .add_systems(Update, tint_asteroids)
+            .add_systems(FixedUpdate, (
+                // Waves hold off while a boss is around
+                (advance_wave, spawn_asteroid).run_if(not(any_with_component::<Boss>)),
                 rotate_asteroids,
-                explode_dead_asteroids,
-            ).in_set(InGameSet::EntityUpdates));
+                steer_swarmer_shards,
+                // Blasts kill first, so swarmers caught in them still release their shards
+                (
+                    detonate_explosive_asteroids,
+                    release_swarmer_shards,
+                    explode_dead_asteroids,
+                ).chain(),
+            ).in_set(InGameSet::EntityUpdates))
+            .add_systems(FixedUpdate, chill_spaceship
+                .after(apply_collision_damage)
+                .in_set(InGameSet::CollisionDetection));
     }
 }
 
 
+fn load_asteroid_assets(
+    mut asteroid_assets: ResMut<AsteroidAssets>,
+    mut loading_assets: ResMut<LoadingAssets>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    *asteroid_assets = AsteroidAssets {
+        variants: asset_server.load(VARIANTS_PATH),
+        shard_mesh: meshes.add(Sphere::new(SHARD_RADIUS).mesh().uv(8, 6)),
+        shard_material: materials.add(StandardMaterial {
+            base_color: SHARD_COLOR,
+            emissive: SHARD_COLOR,
+            ..default()
+        }),
+    };
+    loading_assets.track(&asteroid_assets.variants);
+}
+
+
+fn apply_difficulty(mut spawn_timer: ResMut<SpawnTimer>, settings: Res<Settings>, wave: Res<AsteroidWave>) {
+    let seconds = SPAWN_SECONDS * settings.difficulty.spawn_interval_scale() / wave.spawn_rate();
+    spawn_timer.timer.set_duration(Duration::from_secs_f32(seconds));
+}
+
+
+fn advance_wave(mut wave: ResMut<AsteroidWave>, mut wave_timer: ResMut<WaveTimer>, time: Res<Time>) {
+    wave_timer.timer.tick(time.delta());
+    if wave_timer.timer.just_finished() {
+        wave.number += 1;
+    }
+}
+
+
+fn reset_wave(mut wave: ResMut<AsteroidWave>, mut wave_timer: ResMut<WaveTimer>) {
+    *wave = AsteroidWave::default();
+    *wave_timer = WaveTimer::default();
+}
+
+
 fn spawn_asteroid(
     mut commands: Commands,
     mut spawn_timer: ResMut<SpawnTimer>,
     time: Res<Time>,
+    wave: Res<AsteroidWave>,
     scene_assets: Res<SceneAssets>,
+    asteroid_assets: Res<AsteroidAssets>,
+    asteroid_variants: Res<Assets<AsteroidVariants>>,
 ) {
     spawn_timer.timer.tick(time.delta());
     if !spawn_timer.timer.just_finished() {
         return;
     }
+    let model = scene_assets.get_random_asteroid();
     let translation = DirVector::rng_range(SPAWN_RANGE_X, SPAWN_RANGE_Z);
+    let variant = asteroid_variants.get(&asteroid_assets.variants).and_then(|variants| variants.random(wave.number));
+    spawn_asteroid_entity(&mut commands, model, AsteroidTier::random(), variant, translation);
+}
+
+
+/// Asteroids without a variant are plain ones
+pub fn spawn_asteroid_entity(
+    commands: &mut Commands,
+    model: &ModelAsset,
+    tier: AsteroidTier,
+    variant: Option<&AsteroidVariant>,
+    translation: DirVector,
+) -> Entity {
+    let variant = variant.copied().unwrap_or_default();
+    let mut transform = model.transform(translation.get_transform());
+    transform.scale *= tier.scale();
     let velocity = DirVector::rng_unit(Some(VELOCITY_SCALAR));
 
-    commands.spawn((
+    let mut asteroid = commands.spawn((
         MovingObjectBundle {
             velocity: Velocity::linear(velocity.value),
-            collider: Collider::ball(RADIUS),
-            ..default()
-        },
-        SceneBundle {
-            scene: scene_assets.get_random_asteroid(),
-            transform: translation.get_transform(),
+            collider: model.collider(),
             ..default()
         },
+        SpatialBundle::from_transform(transform),
         Asteroid,
-        Health::new(HEALTH),
+        tier,
+        variant.kind,
+        Health::new(tier.health() * variant.health),
         CollisionDamage::new(COLLISION_DAMAGE),
+        ScoreValue((tier.points() as f32 * variant.points).round() as u32),
+        RadarBlip::Asteroid,
+        DespawnPolicy::OutsideArena,
     ));
+    asteroid.with_children(|parent| {
+        parent.spawn(model.scene_bundle());
+    });
+    if let Some((r, g, b)) = variant.tint {
+        asteroid.insert(AsteroidTint(Color::rgb(r, g, b)));
+    }
+    if let AsteroidKind::Metallic { missile_damage } = variant.kind {
+        asteroid.insert(MissileArmor(missile_damage));
+    }
+    asteroid.id()
 }
 
 
-fn rotate_asteroids(mut query: Query<&mut Transform, Or<(With<Asteroid>, With<AsteroidParticle>)>>, time: Res<Time>) {
+// Catches the meshes of asteroid scenes as they get spawned, a few frames after the asteroid.
+// Tinted copies are shared by every mesh with the same material and tint
+fn tint_asteroids(
+    mut commands: Commands,
+    query: Query<(Entity, &Handle<StandardMaterial>), Added<Handle<StandardMaterial>>>,
+    parent_query: Query<&Parent>,
+    tint_query: Query<&AsteroidTint>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut tinted_materials: Local<HashMap<(AssetId<StandardMaterial>, [u8; 4]), Handle<StandardMaterial>>>,
+) {
+    for (entity, handle) in query.iter() {
+        let Some(&AsteroidTint(tint)) = parent_query.iter_ancestors(entity).find_map(|ancestor| tint_query.get(ancestor).ok()) else {
+            continue;
+        };
+        let key = (handle.id(), tint.as_rgba_u8());
+        let tinted = match tinted_materials.get(&key) {
+            Some(tinted) => tinted.clone(),
+            None => {
+                let Some(material) = materials.get(handle).cloned() else { continue };
+                let base = material.base_color;
+                let tinted = materials.add(StandardMaterial {
+                    base_color: Color::rgba(base.r() * tint.r(), base.g() * tint.g(), base.b() * tint.b(), base.a()),
+                    ..material
+                });
+                tinted_materials.insert(key, tinted.clone());
+                tinted
+            },
+        };
+        commands.entity(entity).insert(tinted);
+    }
+}
+
+
+fn rotate_asteroids(
+    mut query: Query<&mut Transform, (Or<(With<Asteroid>, With<AsteroidParticle>)>, Without<Idle>)>,
+    time: Res<Time>,
+) {
     for mut transform in query.iter_mut() {
         transform.rotate_local_z(ROTATION_SPEED * time.delta_seconds());
     }
 }
 
 
+fn detonate_explosive_asteroids(
+    mut query: Query<(Entity, &mut Health, &GlobalTransform, Option<&AsteroidKind>), (Without<Invulnerable>, Without<Idle>)>,
+    mut collision_damage_event_writer: EventWriter<CollisionDamageEvent>,
+    mut camera_shake_event_writer: EventWriter<CameraShakeEvent>,
+    mut particle_burst_event_writer: EventWriter<ParticleBurstEvent>,
+) {
+    // Explosives killed by a blast go off in the next pass, so whole chains detonate within the tick
+    let mut detonated: Vec<Entity> = Vec::new();
+    loop {
+        let blasts: Vec<(Entity, Vec3, f32, f32)> = query.iter()
+            .filter(|(entity, health, ..)| health.value <= 0. && !detonated.contains(entity))
+            .filter_map(|(entity, _, transform, kind)| match kind {
+                Some(&AsteroidKind::Explosive { radius, damage }) => Some((entity, transform.translation(), radius, damage)),
+                _ => None,
+            })
+            .collect();
+        if blasts.is_empty() {
+            break;
+        }
+        for &(blast, center, radius, damage) in blasts.iter() {
+            detonated.push(blast);
+            camera_shake_event_writer.send(CameraShakeEvent { trauma: BLAST_TRAUMA, origin: Some(center) });
+            particle_burst_event_writer.send(ParticleBurstEvent {
+                kind: ParticleEffectKind::ShipDeath,
+                translation: center,
+                direction: Vec3::ZERO,
+            });
+            for (entity, mut health, transform, _) in query.iter_mut() {
+                if health.value <= 0. {
+                    continue;
+                }
+                let distance = transform.translation().distance(center);
+                if distance > radius {
+                    continue;
+                }
+                let amount = damage * (1. - distance / radius);
+                health.value -= amount;
+                collision_damage_event_writer.send(CollisionDamageEvent { entity, amount });
+            }
+        }
+    }
+}
+
+
+fn release_swarmer_shards(
+    mut commands: Commands,
+    query: Query<(&Health, &GlobalTransform, &AsteroidKind), With<Asteroid>>,
+    asteroid_assets: Res<AsteroidAssets>,
+) {
+    for (health, transform, &kind) in query.iter() {
+        let AsteroidKind::Swarmer { shards, speed, turn_rate, damage } = kind else { continue };
+        if health.value > 0. {
+            continue;
+        }
+        for _ in 0..shards {
+            let velocity = DirVector::rng_unit(Some(speed));
+            commands.spawn((
+                MovingObjectBundle {
+                    velocity: Velocity::linear(velocity.value),
+                    collider: Collider::ball(SHARD_RADIUS),
+                    ..default()
+                },
+                PbrBundle {
+                    mesh: asteroid_assets.shard_mesh.clone(),
+                    material: asteroid_assets.shard_material.clone(),
+                    transform: Transform::from_translation(transform.translation() + velocity.value.normalize_or_zero()),
+                    ..default()
+                },
+                SwarmerShard { speed, turn_rate },
+                Health::new(SHARD_HEALTH),
+                CollisionDamage::new(damage),
+                RadarBlip::Asteroid,
+                Lifetime::new(SHARD_LIFETIME).with_fade(LifetimeFade::Shrink, SHARD_SHRINK_FRACTION),
+                DisposableEntity,
+                DespawnPolicy::OutsideArena,
+            ));
+        }
+    }
+}
+
+
+// Shards turn their velocity towards the ship, keeping their speed
+fn steer_swarmer_shards(
+    mut query: Query<(&SwarmerShard, &GlobalTransform, &mut Velocity)>,
+    ship_query: Query<&GlobalTransform, With<Spaceship>>,
+    time: Res<Time>,
+) {
+    let Ok(ship_transform) = ship_query.get_single() else { return };
+    let target = ship_transform.translation();
+    for (shard, transform, mut velocity) in query.iter_mut() {
+        let offset = target - transform.translation();
+        let desired = Vec3::new(offset.x, 0., offset.z).normalize_or_zero() * shard.speed;
+        let steered = velocity.linvel.lerp(desired, (shard.turn_rate * time.delta_seconds()).min(1.));
+        velocity.linvel = steered.normalize_or_zero() * shard.speed;
+    }
+}
+
+
 fn explode_dead_asteroids(
     mut commands: Commands,
-    query: Query<(Entity, &Health), With<Asteroid>>,
+    query: Query<(Entity, &Health, &GlobalTransform, &ScoreValue, &AsteroidTier), With<Asteroid>>,
     children_query: Query<&Children>,
-    mesh_query: Query<&Handle<Mesh>>,
-    mut score_change_event_writer: EventWriter<GameScoreChangeEvent>,
+    mesh_query: Query<(&Handle<Mesh>, &Handle<StandardMaterial>, &GlobalTransform)>,
+    meshes: Res<Assets<Mesh>>,
+    mut collider_cache: ResMut<MeshColliderCache>,
+    mut pool: ResMut<EntityPool>,
+    mut target_destroyed_event_writer: EventWriter<TargetDestroyedEvent>,
+    mut camera_shake_event_writer: EventWriter<CameraShakeEvent>,
+    mut particle_burst_event_writer: EventWriter<ParticleBurstEvent>,
+    mut stat_event_writer: EventWriter<StatEvent>,
 ) {
-    for (entity, health) in query.iter() {
+    for (entity, health, transform, &ScoreValue(points), &tier) in query.iter() {
         if health.value > 0. {
             continue;
         }
+        // Every mesh of the model flies off as a pooled debris entity, the model itself is despawned
         for child in children_query.iter_descendants(entity) {
-            if mesh_query.get(child).is_err() {
+            let Ok((mesh, material, child_transform)) = mesh_query.get(child) else {
                 continue;  // Not interested in meshless entities
-            }
-            let Some(mut child_commands) = commands.get_entity(child) else { continue };
+            };
+            let collider = collider_cache.mesh_collider(mesh, &meshes)
+                .unwrap_or_else(|| Collider::ball(DEBRIS_RADIUS));
             let velocity = DirVector::rng_unit(Some(VELOCITY_SCALAR));
-            child_commands.remove_parent_in_place();
-            child_commands.insert((
-                MovingObjectBundle {
-                    velocity: Velocity::linear(velocity.value),
-                    collider: Collider::ball(RADIUS / 10.),
-                    ..default()
-                },
+            let debris = pool.acquire(&mut commands, PoolKind::Debris);
+            let mut debris_commands = commands.entity(debris.entity);
+            if !debris.recycled {
+                debris_commands.insert(MovingObjectBundle::default());
+            }
+            debris_commands.insert((
+                mesh.clone(),
+                material.clone(),
+                SpatialBundle::from_transform(child_transform.compute_transform()),
+                // Recycled debris starts interpolating from its new position
+                InterpolatedTransform::default(),
+                Velocity::linear(velocity.value),
+                collider,
                 AsteroidParticle,
+                Lifetime::new(DEBRIS_LIFETIME).with_fade(LifetimeFade::Fade, DEBRIS_FADE_FRACTION),
                 DisposableEntity,
             ));
         }
-        score_change_event_writer.send(GameScoreChangeEvent {
-            score_delta: 1,
-            clear_score: false,
+        target_destroyed_event_writer.send(TargetDestroyedEvent {
+            points,
+            translation: transform.translation(),
+        });
+        stat_event_writer.send(StatEvent::AsteroidDestroyed(tier));
+        camera_shake_event_writer.send(CameraShakeEvent {
+            trauma: EXPLOSION_TRAUMA,
+            origin: Some(transform.translation()),
+        });
+        particle_burst_event_writer.send(ParticleBurstEvent {
+            kind: ParticleEffectKind::AsteroidExplosion,
+            translation: transform.translation(),
+            direction: Vec3::ZERO,
         });
         let Some(asteroid_commands) = commands.get_entity(entity) else { continue };
         asteroid_commands.despawn_recursive();
     }
 }
+
+
+fn chill_spaceship(
+    mut commands: Commands,
+    mut collision_event_reader: EventReader<CollisionEvent>,
+    asteroid_query: Query<&AsteroidKind>,
+    ship_query: Query<(), With<Spaceship>>,
+) {
+    for &event in collision_event_reader.read() {
+        let CollisionEvent::Started(entity1, entity2, _) = event else { continue };
+        let (asteroid, ship) = if asteroid_query.contains(entity1) { (entity1, entity2) } else { (entity2, entity1) };
+        let Ok(&AsteroidKind::Ice { slow, seconds }) = asteroid_query.get(asteroid) else { continue };
+        if !ship_query.contains(ship) {
+            continue;
+        }
+        // A fresh hit restarts the chill
+        commands.entity(ship).insert(Chilled::new(slow, seconds));
+    }
+}
+
+
+fn spawn_command(
+    In(args): In<ConsoleArgs>,
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+    asteroid_assets: Res<AsteroidAssets>,
+    asteroid_variants: Res<Assets<AsteroidVariants>>,
+    wave: Res<AsteroidWave>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    if args.first().map(String::as_str) != Some("asteroid") {
+        log.error("usage: spawn asteroid <n> [tier] [variant]");
+        return;
+    }
+    let Some(count) = parse_arg::<u32>(&args, 1, "n", &mut log) else { return };
+    let tier = match args.get(2) {
+        Some(name) => match AsteroidTier::parse(name) {
+            Some(tier) => Some(tier),
+            None => {
+                log.error(format!("unknown tier '{}', expected small, medium or large", name));
+                return;
+            },
+        },
+        None => None,
+    };
+    let variants = asteroid_variants.get(&asteroid_assets.variants);
+    let variant = match args.get(3) {
+        Some(name) => match variants.and_then(|variants| variants.find(name)) {
+            Some(variant) => Some(variant),
+            None => {
+                log.error(format!("unknown variant '{}', expected one of {}", name, AsteroidKind::NAMES.join(", ")));
+                return;
+            },
+        },
+        None => None,
+    };
+    let count = count.min(MAX_CONSOLE_SPAWN);
+    for _ in 0..count {
+        let model = scene_assets.get_random_asteroid();
+        let translation = DirVector::rng_range(SPAWN_RANGE_X, SPAWN_RANGE_Z);
+        let variant = variant.or_else(|| variants.and_then(|variants| variants.random(wave.number)));
+        spawn_asteroid_entity(&mut commands, model, tier.unwrap_or_else(AsteroidTier::random), variant, translation);
+    }
+    log.info(format!("spawned {} asteroids", count));
+}
+
+
+fn wave_command(
+    In(args): In<ConsoleArgs>,
+    mut wave: ResMut<AsteroidWave>,
+    mut wave_timer: ResMut<WaveTimer>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let Some(number) = parse_arg::<u32>(&args, 0, "n", &mut log) else { return };
+    if number == 0 {
+        log.error("waves start at 1");
+        return;
+    }
+    *wave = AsteroidWave { number };
+    *wave_timer = WaveTimer::default();
+    log.info(format!("wave {}", number));
+}
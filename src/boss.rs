@@ -0,0 +1,621 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use serde::{de::Error, Deserialize, Deserializer};
+
+use crate::schedule::InGameSet;
+use crate::asset_loader::{LoadingAssets, ModelAsset, RonAssetLoader, SceneAssets};
+use crate::asteroids::{spawn_asteroid_entity, AsteroidTier, AsteroidWave};
+use crate::movement::{DirVector, MovingObjectBundle};
+use crate::interpolation::InterpolatedTransform;
+use crate::health::{Health, Invulnerable};
+use crate::collision_detection::{apply_collision_damage, CollisionDamage};
+use crate::despawn_routine::{DespawnPolicy, DisposableEntity};
+use crate::lifetime::{Lifetime, LifetimeFade};
+use crate::scoring::TargetDestroyedEvent;
+use crate::hud::{GameScoreChangeEvent, RadarBlip};
+use crate::camera::CameraShakeEvent;
+use crate::particles::{ParticleBurstEvent, ParticleEffectKind};
+use crate::spaceship::{HullStats, Spaceship};
+use crate::console::{ConsoleArgs, ConsoleCommandsExt, ConsoleLog};
+
+const BOSSES_PATH: &'static str = "game.bosses.ron";
+
+const SPAWN_TRANSLATION: Vec3 = Vec3::new(0., 0., 60.);
+const TURN_RATE: f32 = 1.2;
+// Share of the keep distance the boss may drift inside of it before backing off
+const KEEP_DISTANCE_SLACK: f32 = 0.8;
+const STRAFE_SCALAR: f32 = 0.5;
+
+const PROJECTILE_LIFETIME: f32 = 4.;
+// Gap between the reach of the parts and the summoned asteroids
+const SUMMON_CLEARANCE: f32 = 4.;
+
+const PART_DESTROYED_TRAUMA: f32 = 0.4;
+const PHASE_CHANGE_TRAUMA: f32 = 0.5;
+const DEFEAT_TRAUMA: f32 = 1.;
+
+const REWARD_RADIUS: f32 = 1.5;
+const REWARD_LIFETIME: f32 = 20.;
+const REWARD_SHRINK_FRACTION: f32 = 0.2;
+const REWARD_COLOR: Color = Color::rgb(0.3, 1., 0.6);
+
+// Boss parts never hit the boss's own projectiles, and projectiles don't hit each other
+const BOSS_GROUP: Group = Group::GROUP_2;
+const PROJECTILE_GROUP: Group = Group::GROUP_3;
+// Summoned asteroids don't hit the boss that launched them
+const SUMMONED_GROUP: Group = Group::GROUP_1;
+
+
+/// When a boss shows up, checked every time a new asteroid wave starts
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum BossTrigger {
+    Wave(u32),
+    EveryWaves { first: u32, interval: u32 },
+}
+
+
+impl BossTrigger {
+    fn matches(self, wave: u32) -> bool {
+        match self {
+            Self::Wave(number) => wave == number,
+            Self::EveryWaves { first, interval } => wave >= first && (wave - first) % interval.max(1) == 0,
+        }
+    }
+}
+
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum AttackPattern {
+    /// Projectiles fanned out towards the ship, `spread` degrees apart
+    Aimed { count: u32, spread: f32 },
+    /// Projectiles in every direction, every other volley turned by half a gap
+    Radial { count: u32 },
+    /// Small asteroids launched from around the boss
+    Summon { count: u32 },
+}
+
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BossPhase {
+    /// Fraction of the total health at or below which the phase starts
+    pub below: f32,
+    pub attack: AttackPattern,
+    /// Seconds between attacks
+    pub cooldown: f32,
+    pub speed: f32,
+}
+
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BossPartDefinition {
+    /// Position on the play plane (x, z) relative to the boss center
+    pub offset: (f32, f32),
+    pub radius: f32,
+    pub health: f32,
+    #[serde(default)]
+    pub core: bool,
+    /// Awarded when the part is destroyed
+    #[serde(default)]
+    pub points: u32,
+}
+
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct ProjectileDefinition {
+    pub speed: f32,
+    pub damage: f32,
+}
+
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct RewardDefinition {
+    pub points: u32,
+    pub heal: f32,
+}
+
+
+fn default_scale() -> f32 { 1. }
+
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct BossDefinition {
+    pub id: String,
+    /// Message key of the name shown on the HUD
+    pub name_key: String,
+    /// Name of a model in the model manifest
+    pub model: String,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    pub trigger: BossTrigger,
+    pub points: u32,
+    pub collision_damage: f32,
+    /// Distance to the ship the boss tries to hold
+    pub keep_distance: f32,
+    pub projectile: ProjectileDefinition,
+    pub parts: Vec<BossPartDefinition>,
+    #[serde(deserialize_with = "deserialize_phases")]
+    pub phases: Vec<BossPhase>,
+    pub reward: RewardDefinition,
+}
+
+
+impl BossDefinition {
+    /// Distance from the boss center to the farthest edge of its parts
+    fn reach(&self) -> f32 {
+        self.parts.iter()
+            .map(|part| Vec2::new(part.offset.0, part.offset.1).length() + part.radius)
+            .fold(0., f32::max)
+    }
+}
+
+
+// Phases are looked up from the last one, so they are kept sorted by `below`, highest first
+fn deserialize_phases<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<BossPhase>, D::Error> {
+    let mut phases = Vec::<BossPhase>::deserialize(deserializer)?;
+    if let Some(phase) = phases.iter().find(|phase| !phase.below.is_finite()) {
+        return Err(D::Error::custom(format!("phase threshold {} is not a number", phase.below)));
+    }
+    phases.sort_by(|a, b| b.below.total_cmp(&a.below));
+    Ok(phases)
+}
+
+
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct BossDefinitions {
+    pub bosses: Vec<BossDefinition>,
+}
+
+
+#[derive(Resource, Default, Debug)]
+pub struct BossAssets {
+    pub definitions: Handle<BossDefinitions>,
+    pub reward_mesh: Handle<Mesh>,
+    pub reward_material: Handle<StandardMaterial>,
+}
+
+
+#[derive(Component, Debug)]
+pub struct Boss {
+    definition: BossDefinition,
+    health: f32,
+    max_health: f32,
+    phase: usize,
+    attack_timer: Timer,
+    volleys: u32,
+}
+
+
+impl Boss {
+    fn new(definition: &BossDefinition) -> Self {
+        let mut boss = Self {
+            definition: definition.clone(),
+            health: 0.,
+            max_health: definition.parts.iter().map(|part| part.health).sum(),
+            phase: 0,
+            attack_timer: Timer::from_seconds(1., TimerMode::Once),
+            volleys: 0,
+        };
+        boss.health = boss.max_health;
+        boss.enter_phase(0);
+        boss
+    }
+
+    pub fn name_key(&self) -> &str {
+        &self.definition.name_key
+    }
+
+    pub fn health_fraction(&self) -> f32 {
+        if self.max_health > 0. { (self.health / self.max_health).clamp(0., 1.) } else { 0. }
+    }
+
+    /// Current phase counted from 1, and the number of phases
+    pub fn phase_progress(&self) -> (usize, usize) {
+        (self.phase + 1, self.definition.phases.len().max(1))
+    }
+
+    fn current_phase(&self) -> Option<&BossPhase> {
+        self.definition.phases.get(self.phase)
+    }
+
+    fn enter_phase(&mut self, phase: usize) {
+        self.phase = phase;
+        let cooldown = self.current_phase().map_or(1., |phase| phase.cooldown);
+        self.attack_timer = Timer::from_seconds(cooldown.max(0.1), TimerMode::Repeating);
+    }
+
+    // Phases only move forward, even if the boss gets healed
+    fn phase_for_health(&self) -> usize {
+        let fraction = self.health_fraction();
+        self.definition.phases.iter()
+            .rposition(|phase| fraction <= phase.below)
+            .unwrap_or(0)
+            .max(self.phase)
+    }
+}
+
+
+/// Hitbox of a boss with its own health, a child of the boss entity
+#[derive(Component, Debug)]
+pub struct BossPart {
+    core: bool,
+    points: u32,
+}
+
+
+#[derive(Component, Debug)]
+pub struct BossProjectile;
+
+
+/// Dropped by a defeated boss, collected by flying into it
+#[derive(Component, Debug)]
+pub struct RewardPickup(RewardDefinition);
+
+
+pub struct BossPlugin;
+
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<BossAssets>()
+            .init_asset::<BossDefinitions>()
+            .register_asset_loader(RonAssetLoader::<BossDefinitions>::new(&["bosses.ron"]))
+            .add_console_command("boss", "boss [id] - starts a fight with the given boss, or the first one", &[], boss_command)
+            .add_systems(Startup, load_boss_assets)
+            .add_systems(FixedUpdate, (
+                trigger_bosses,
+                move_bosses,
+                boss_attacks,
+            ).chain().in_set(InGameSet::EntityUpdates))
+            .add_systems(FixedUpdate, (
+                update_bosses.after(apply_collision_damage),
+                collect_rewards,
+            ).in_set(InGameSet::CollisionDetection));
+    }
+}
+
+
+fn load_boss_assets(
+    mut boss_assets: ResMut<BossAssets>,
+    mut loading_assets: ResMut<LoadingAssets>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    *boss_assets = BossAssets {
+        definitions: asset_server.load(BOSSES_PATH),
+        reward_mesh: meshes.add(Sphere::new(REWARD_RADIUS).mesh().uv(16, 12)),
+        reward_material: materials.add(StandardMaterial {
+            base_color: REWARD_COLOR,
+            emissive: REWARD_COLOR,
+            ..default()
+        }),
+    };
+    loading_assets.track(&boss_assets.definitions);
+}
+
+
+fn trigger_bosses(
+    mut commands: Commands,
+    wave: Res<AsteroidWave>,
+    mut last_wave: Local<u32>,
+    boss_query: Query<(), With<Boss>>,
+    boss_assets: Res<BossAssets>,
+    boss_definitions: Res<Assets<BossDefinitions>>,
+    scene_assets: Res<SceneAssets>,
+) {
+    if wave.number == *last_wave {
+        return;
+    }
+    *last_wave = wave.number;
+    if !boss_query.is_empty() {
+        return;
+    }
+    let Some(definitions) = boss_definitions.get(&boss_assets.definitions) else { return };
+    let Some(definition) = definitions.bosses.iter().find(|boss| boss.trigger.matches(wave.number)) else { return };
+    spawn_boss(&mut commands, definition, &scene_assets);
+}
+
+
+fn spawn_boss(commands: &mut Commands, definition: &BossDefinition, scene_assets: &SceneAssets) -> Option<Entity> {
+    let Some(model) = scene_assets.get(&definition.model) else {
+        error!("Boss '{}' uses the unknown model '{}'", definition.id, definition.model);
+        return None;
+    };
+    // Armor on the core holds as long as any other part is left
+    let shielded = definition.parts.iter().any(|part| !part.core);
+    let boss = commands.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(SPAWN_TRANSLATION).looking_to(Vec3::NEG_Z, Vec3::Y)),
+        RigidBody::KinematicVelocityBased,
+        Velocity::zero(),
+        InterpolatedTransform::default(),
+        Boss::new(definition),
+        RadarBlip::Enemy,
+        DisposableEntity,
+        DespawnPolicy::Never,
+    )).with_children(|parent| {
+        let mut scene = model.scene_bundle();
+        scene.transform.scale = Vec3::splat(model.scale * definition.scale);
+        parent.spawn(scene);
+        for part in definition.parts.iter() {
+            let mut part_commands = parent.spawn((
+                TransformBundle::from_transform(Transform::from_xyz(part.offset.0, 0., part.offset.1)),
+                Collider::ball(part.radius),
+                CollisionGroups::new(BOSS_GROUP, Group::ALL.difference(PROJECTILE_GROUP)),
+                ActiveEvents::COLLISION_EVENTS,
+                // The ship is kinematic as well
+                ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_KINEMATIC,
+                BossPart { core: part.core, points: part.points },
+                Health::new(part.health),
+                CollisionDamage::new(definition.collision_damage),
+            ));
+            if part.core && shielded {
+                part_commands.insert(Invulnerable);
+            }
+        }
+    }).id();
+    Some(boss)
+}
+
+
+// Closes in on the ship up to the keep distance, then circles it while facing it
+fn move_bosses(
+    mut query: Query<(&mut Velocity, &Transform, &Boss)>,
+    ship_query: Query<&Transform, (With<Spaceship>, Without<Boss>)>,
+) {
+    let ship = ship_query.get_single().ok();
+    for (mut velocity, transform, boss) in query.iter_mut() {
+        let Some(ship) = ship else {
+            *velocity = Velocity::zero();
+            continue;
+        };
+        let speed = boss.current_phase().map_or(0., |phase| phase.speed);
+        let offset = ship.translation - transform.translation;
+        let offset = Vec3::new(offset.x, 0., offset.z);
+        let distance = offset.length();
+        let direction = offset.normalize_or_zero();
+        let keep_distance = boss.definition.keep_distance;
+        velocity.linvel = if distance > keep_distance {
+            direction * speed
+        } else if distance < keep_distance * KEEP_DISTANCE_SLACK {
+            -direction * speed
+        } else {
+            direction.cross(Vec3::Y) * speed * STRAFE_SCALAR
+        };
+
+        let forward = transform.rotation * Vec3::Z;
+        let yaw_error = direction.x.atan2(direction.z) - forward.x.atan2(forward.z);
+        let yaw_error = (yaw_error + PI).rem_euclid(TAU) - PI;
+        velocity.angvel = Vec3::Y * yaw_error.clamp(-1., 1.) * TURN_RATE;
+    }
+}
+
+
+fn boss_attacks(
+    mut commands: Commands,
+    mut query: Query<(&mut Boss, &Transform)>,
+    ship_query: Query<&Transform, (With<Spaceship>, Without<Boss>)>,
+    scene_assets: Res<SceneAssets>,
+    time: Res<Time>,
+) {
+    let Ok(ship) = ship_query.get_single() else { return };
+    for (mut boss, transform) in query.iter_mut() {
+        boss.attack_timer.tick(time.delta());
+        if !boss.attack_timer.just_finished() {
+            continue;
+        }
+        let Some(attack) = boss.current_phase().map(|phase| phase.attack) else { continue };
+        let projectile = boss.definition.projectile;
+        let aim = Vec3::new(ship.translation.x - transform.translation.x, 0., ship.translation.z - transform.translation.z)
+            .try_normalize()
+            .unwrap_or(Vec3::NEG_Z);
+        let volley = boss.volleys;
+        boss.volleys += 1;
+
+        match attack {
+            AttackPattern::Aimed { count, spread } => {
+                let middle = (count.max(1) - 1) as f32 / 2.;
+                for i in 0..count {
+                    let angle = (i as f32 - middle) * spread.to_radians();
+                    let direction = Quat::from_rotation_y(angle) * aim;
+                    spawn_projectile(&mut commands, scene_assets.get_random_bullet(), transform.translation, direction, projectile);
+                }
+            },
+            AttackPattern::Radial { count } => {
+                let gap = TAU / count.max(1) as f32;
+                let turn = if volley % 2 == 0 { 0. } else { gap / 2. };
+                for i in 0..count {
+                    let direction = Quat::from_rotation_y(turn + gap * i as f32) * aim;
+                    spawn_projectile(&mut commands, scene_assets.get_random_bullet(), transform.translation, direction, projectile);
+                }
+            },
+            AttackPattern::Summon { count } => {
+                let mut rng = rand::thread_rng();
+                let distance = boss.definition.reach() + SUMMON_CLEARANCE;
+                for _ in 0..count {
+                    let angle = rng.gen_range(0.0..TAU);
+                    let translation = transform.translation + Quat::from_rotation_y(angle) * Vec3::Z * distance;
+                    let asteroid = spawn_asteroid_entity(&mut commands, scene_assets.get_random_asteroid(), AsteroidTier::Small, None, DirVector::new(translation));
+                    commands.entity(asteroid).insert(CollisionGroups::new(SUMMONED_GROUP, Group::ALL.difference(BOSS_GROUP)));
+                }
+            },
+        }
+    }
+}
+
+
+fn spawn_projectile(commands: &mut Commands, model: &ModelAsset, translation: Vec3, direction: Vec3, projectile: ProjectileDefinition) {
+    let velocity = direction * projectile.speed;
+    commands.spawn((
+        MovingObjectBundle {
+            velocity: Velocity::linear(velocity),
+            collider: model.collider(),
+            ..default()
+        },
+        CollisionGroups::new(PROJECTILE_GROUP, Group::ALL.difference(BOSS_GROUP | PROJECTILE_GROUP)),
+        SpatialBundle::from_transform(model.transform(DirVector::new(translation).get_transform())),
+        BossProjectile,
+        // Anything it touches takes it out
+        Health::new(1.),
+        CollisionDamage::new(projectile.damage),
+        Lifetime::new(PROJECTILE_LIFETIME),
+        DisposableEntity,
+    )).with_children(|parent| {
+        parent.spawn(model.scene_bundle());
+    });
+}
+
+
+// Breaks off destroyed parts, moves through the phases and finishes off defeated bosses
+fn update_bosses(
+    mut commands: Commands,
+    mut boss_query: Query<(Entity, &mut Boss, &GlobalTransform, Option<&Children>)>,
+    part_query: Query<(&BossPart, &Health, &GlobalTransform, Has<Invulnerable>)>,
+    boss_assets: Res<BossAssets>,
+    mut target_destroyed_event_writer: EventWriter<TargetDestroyedEvent>,
+    mut camera_shake_event_writer: EventWriter<CameraShakeEvent>,
+    mut particle_burst_event_writer: EventWriter<ParticleBurstEvent>,
+) {
+    for (entity, mut boss, transform, children) in boss_query.iter_mut() {
+        let mut health = 0.;
+        let mut shields = 0;
+        let mut cores = Vec::new();
+        for &child in children.map_or(&[][..], |children| &children[..]) {
+            let Ok((part, part_health, part_transform, invulnerable)) = part_query.get(child) else { continue };
+            if part_health.value > 0. {
+                health += part_health.value;
+                if part.core {
+                    cores.push((child, invulnerable));
+                } else {
+                    shields += 1;
+                }
+                continue;
+            }
+            let translation = part_transform.translation();
+            if part.points > 0 {
+                target_destroyed_event_writer.send(TargetDestroyedEvent { points: part.points, translation });
+            }
+            camera_shake_event_writer.send(CameraShakeEvent { trauma: PART_DESTROYED_TRAUMA, origin: Some(translation) });
+            particle_burst_event_writer.send(ParticleBurstEvent {
+                kind: ParticleEffectKind::AsteroidExplosion,
+                translation,
+                direction: Vec3::ZERO,
+            });
+            commands.entity(child).despawn_recursive();
+        }
+        boss.health = health;
+
+        let has_core = boss.definition.parts.iter().any(|part| part.core);
+        let defeated = if has_core { cores.is_empty() } else { shields == 0 };
+        let translation = transform.translation();
+        if defeated {
+            target_destroyed_event_writer.send(TargetDestroyedEvent { points: boss.definition.points, translation });
+            camera_shake_event_writer.send(CameraShakeEvent { trauma: DEFEAT_TRAUMA, origin: None });
+            particle_burst_event_writer.send(ParticleBurstEvent {
+                kind: ParticleEffectKind::ShipDeath,
+                translation,
+                direction: Vec3::ZERO,
+            });
+            spawn_reward(&mut commands, &boss_assets, boss.definition.reward, translation);
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        if shields == 0 {
+            for &(core, invulnerable) in cores.iter() {
+                if invulnerable {
+                    commands.entity(core).remove::<Invulnerable>();
+                }
+            }
+        }
+        let phase = boss.phase_for_health();
+        if phase != boss.phase {
+            boss.enter_phase(phase);
+            camera_shake_event_writer.send(CameraShakeEvent { trauma: PHASE_CHANGE_TRAUMA, origin: Some(translation) });
+            particle_burst_event_writer.send(ParticleBurstEvent {
+                kind: ParticleEffectKind::MissileImpact,
+                translation,
+                direction: Vec3::ZERO,
+            });
+        }
+    }
+}
+
+
+fn spawn_reward(commands: &mut Commands, boss_assets: &BossAssets, reward: RewardDefinition, translation: Vec3) {
+    commands.spawn((
+        PbrBundle {
+            mesh: boss_assets.reward_mesh.clone(),
+            material: boss_assets.reward_material.clone(),
+            transform: Transform::from_translation(translation),
+            ..default()
+        },
+        Collider::ball(REWARD_RADIUS),
+        Sensor,
+        ActiveEvents::COLLISION_EVENTS,
+        // The pickup has no body, and the ship's is kinematic
+        ActiveCollisionTypes::all(),
+        RewardPickup(reward),
+        RadarBlip::Pickup,
+        Lifetime::new(REWARD_LIFETIME).with_fade(LifetimeFade::Shrink, REWARD_SHRINK_FRACTION),
+        DisposableEntity,
+        DespawnPolicy::Never,
+    ));
+}
+
+
+fn collect_rewards(
+    mut commands: Commands,
+    mut collision_event_reader: EventReader<CollisionEvent>,
+    reward_query: Query<&RewardPickup>,
+    mut ship_query: Query<(&mut Health, &HullStats), With<Spaceship>>,
+    mut score_change_event_writer: EventWriter<GameScoreChangeEvent>,
+) {
+    for &event in collision_event_reader.read() {
+        let CollisionEvent::Started(entity1, entity2, _) = event else { continue };
+        let (pickup, ship) = if reward_query.contains(entity1) { (entity1, entity2) } else { (entity2, entity1) };
+        let Ok(&RewardPickup(reward)) = reward_query.get(pickup) else { continue };
+        let Ok((mut health, hull)) = ship_query.get_mut(ship) else { continue };
+        health.value = (health.value + reward.heal).min(hull.health);
+        score_change_event_writer.send(GameScoreChangeEvent { score_delta: reward.points as i32, clear_score: false });
+        let Some(pickup_commands) = commands.get_entity(pickup) else { continue };
+        pickup_commands.despawn_recursive();
+    }
+}
+
+
+fn boss_command(
+    In(args): In<ConsoleArgs>,
+    mut commands: Commands,
+    boss_query: Query<(), With<Boss>>,
+    boss_assets: Res<BossAssets>,
+    boss_definitions: Res<Assets<BossDefinitions>>,
+    scene_assets: Res<SceneAssets>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    if !boss_query.is_empty() {
+        log.error("a boss fight is already on");
+        return;
+    }
+    let Some(definitions) = boss_definitions.get(&boss_assets.definitions) else {
+        log.error("boss definitions aren't loaded");
+        return;
+    };
+    let definition = match args.first() {
+        Some(id) => definitions.bosses.iter().find(|boss| boss.id == *id),
+        None => definitions.bosses.first(),
+    };
+    let Some(definition) = definition else {
+        let ids: Vec<&str> = definitions.bosses.iter().map(|boss| boss.id.as_str()).collect();
+        log.error(format!("unknown boss, expected one of: {}", ids.join(", ")));
+        return;
+    };
+    if spawn_boss(&mut commands, definition, &scene_assets).is_some() {
+        log.info(format!("spawned {}", definition.id));
+    } else {
+        log.error(format!("boss '{}' has an unknown model", definition.id));
+    }
+}
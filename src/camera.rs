@@ -1,21 +1,178 @@
 use bevy::prelude::*;
+use rand::Rng;
+
+use crate::interpolation::interpolate_transforms;
+use crate::settings::{CameraMode, Settings};
+use crate::spaceship::Spaceship;
+use crate::collision_detection::CollisionDamageEvent;
+use crate::accessibility::Accessibility;
 
 const STARTING_TRANSLATION: Vec3 = Vec3::new(0., 80., 0.);
 
+const FOLLOW_DEAD_ZONE: Vec2 = Vec2::new(15., 10.);
+const FOLLOW_SMOOTHNESS: f32 = 4.;
+const LOOK_AHEAD_SECONDS: f32 = 0.8;
+
+const ZOOM_PER_SPEED: f32 = 0.6;
+const ZOOM_MAX_HEIGHT: f32 = 110.;
+const ZOOM_SMOOTHNESS: f32 = 2.;
+
+const TRAUMA_DECAY: f32 = 1.2;
+const SHAKE_MAX_OFFSET: f32 = 2.5;
+const SHAKE_MAX_ANGLE: f32 = 0.05;
+const SHAKE_FALLOFF_DISTANCE: f32 = 60.;
+const SHIP_DAMAGE_TRAUMA: f32 = 0.01;
+
+
+#[derive(Component, Default, Debug)]
+pub struct GameCamera {
+    focus: Vec3,
+    height: f32,
+    trauma: f32,
+    ship_velocity: Vec3,
+    last_ship_translation: Option<Vec3>,
+}
+
+
+impl GameCamera {
+    /// Point on the play plane the camera is centered on
+    pub fn focus(&self) -> Vec3 {
+        self.focus
+    }
+}
+
+
+/// Adds trauma to the camera; nearby events (relative to the camera focus) shake it harder
+#[derive(Event, Debug)]
+pub struct CameraShakeEvent {
+    pub trauma: f32,
+    pub origin: Option<Vec3>,
+}
+
 
 pub struct CameraPlugin;
 
 
 impl Plugin for CameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, spawn_camera);
+        app
+            .add_event::<CameraShakeEvent>()
+            .add_systems(Startup, spawn_camera)
+            .add_systems(Update, (
+                shake_on_ship_damage,
+                add_camera_trauma,
+                update_camera_rig,
+                apply_camera_transform,
+            ).chain().after(interpolate_transforms));
     }
 }
 
 
 fn spawn_camera(mut commands: Commands) {
-    commands.spawn(Camera3dBundle {
-        transform: Transform::from_translation(STARTING_TRANSLATION).looking_at(Vec3::ZERO, Vec3::Z),
-        ..default()
-    });
+    commands.spawn((
+        Camera3dBundle {
+            transform: Transform::from_translation(STARTING_TRANSLATION).looking_at(Vec3::ZERO, Vec3::Z),
+            ..default()
+        },
+        GameCamera {
+            height: STARTING_TRANSLATION.y,
+            ..default()
+        },
+    ));
+}
+
+
+fn shake_on_ship_damage(
+    mut collision_damage_event_reader: EventReader<CollisionDamageEvent>,
+    mut camera_shake_event_writer: EventWriter<CameraShakeEvent>,
+    query: Query<(), With<Spaceship>>,
+) {
+    for &CollisionDamageEvent { entity, amount } in collision_damage_event_reader.read() {
+        if query.contains(entity) {
+            camera_shake_event_writer.send(CameraShakeEvent {
+                trauma: amount * SHIP_DAMAGE_TRAUMA,
+                origin: None,
+            });
+        }
+    }
+}
+
+
+fn add_camera_trauma(
+    mut camera_shake_event_reader: EventReader<CameraShakeEvent>,
+    mut query: Query<&mut GameCamera>,
+    accessibility: Res<Accessibility>,
+) {
+    if accessibility.reduced_motion {
+        camera_shake_event_reader.clear();
+        return;
+    }
+    let Ok(mut camera) = query.get_single_mut() else { return };
+    for event in camera_shake_event_reader.read() {
+        let falloff = match event.origin {
+            Some(origin) => (1. - origin.distance(camera.focus) / SHAKE_FALLOFF_DISTANCE).max(0.),
+            None => 1.,
+        };
+        camera.trauma = (camera.trauma + event.trauma * falloff).min(1.);
+    }
+}
+
+
+fn update_camera_rig(
+    mut query: Query<&mut GameCamera>,
+    ship_query: Query<&Transform, With<Spaceship>>,
+    settings: Res<Settings>,
+    time: Res<Time>,
+) {
+    let Ok(mut camera) = query.get_single_mut() else { return };
+    let dt = time.delta_seconds();
+    camera.trauma = (camera.trauma - TRAUMA_DECAY * dt).max(0.);
+
+    // The ship is moved kinematically, so its velocity is derived from its translation
+    let ship_translation = ship_query.get_single().ok().map(|transform| transform.translation);
+    camera.ship_velocity = match (ship_translation, camera.last_ship_translation) {
+        (Some(current), Some(last)) if dt > 0. => (current - last) / dt,
+        _ => Vec3::ZERO,
+    };
+    camera.last_ship_translation = ship_translation;
+
+    let target = match (settings.camera_mode, ship_translation) {
+        (CameraMode::Fixed, _) | (_, None) => Vec3::ZERO,
+        (CameraMode::Follow, Some(ship)) => {
+            // Only move once the ship leaves the dead zone around the current focus
+            let offset = ship - camera.focus;
+            let excess = Vec3::new(
+                offset.x.signum() * (offset.x.abs() - FOLLOW_DEAD_ZONE.x).max(0.),
+                0.,
+                offset.z.signum() * (offset.z.abs() - FOLLOW_DEAD_ZONE.y).max(0.),
+            );
+            camera.focus + excess
+        },
+        (CameraMode::LookAhead, Some(ship)) => ship + camera.ship_velocity * LOOK_AHEAD_SECONDS,
+    };
+    let target_height = match settings.camera_mode {
+        CameraMode::Fixed => STARTING_TRANSLATION.y,
+        _ => (STARTING_TRANSLATION.y + camera.ship_velocity.length() * ZOOM_PER_SPEED).min(ZOOM_MAX_HEIGHT),
+    };
+
+    // Frame rate independent exponential smoothing
+    let focus = camera.focus;
+    camera.focus = focus.lerp(target, 1. - (-FOLLOW_SMOOTHNESS * dt).exp());
+    let height = camera.height;
+    camera.height = height + (target_height - height) * (1. - (-ZOOM_SMOOTHNESS * dt).exp());
+}
+
+
+fn apply_camera_transform(mut query: Query<(&mut Transform, &GameCamera)>) {
+    let Ok((mut transform, camera)) = query.get_single_mut() else { return };
+    let mut rng = rand::thread_rng();
+
+    // Shake grows with the square of trauma, so small hits barely register
+    let shake = camera.trauma * camera.trauma;
+    let offset = Vec3::new(rng.gen_range(-1.0..1.0), 0., rng.gen_range(-1.0..1.0)) * SHAKE_MAX_OFFSET * shake;
+    let angle = rng.gen_range(-1.0..1.0) * SHAKE_MAX_ANGLE * shake;
+
+    *transform = Transform::from_translation(camera.focus + Vec3::Y * camera.height + offset)
+        .looking_at(camera.focus + offset, Vec3::Z);
+    transform.rotate_local_z(angle);
 }
@@ -2,7 +2,8 @@ use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
 use crate::schedule::InGameSet;
-use crate::health::Health;
+use crate::health::{Health, Invulnerable};
+use crate::spaceship::SpaceshipMissile;
 
 
 #[derive(Component, Default, Debug)]
@@ -16,31 +17,51 @@ impl CollisionDamage {
 }
 
 
+/// Fraction of the damage dealt by the ship's missiles that gets through
+#[derive(Component, Clone, Copy, Debug)]
+pub struct MissileArmor(pub f32);
+
+
+#[derive(Event, Debug)]
+pub struct CollisionDamageEvent {
+    pub entity: Entity,
+    pub amount: f32,
+}
+
+
 pub struct CollisionDetectionPlugin;
 
 
 impl Plugin for CollisionDetectionPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Update, apply_collision_damage.in_set(InGameSet::CollisionDetection));
+            .add_event::<CollisionDamageEvent>()
+            .add_systems(FixedUpdate, apply_collision_damage.in_set(InGameSet::CollisionDetection));
     }
 }
 
 
 pub fn apply_collision_damage(
     mut collision_event_reader: EventReader<CollisionEvent>,
-    mut health_query: Query<&mut Health>,
+    mut health_query: Query<&mut Health, Without<Invulnerable>>,
     collision_damage_query: Query<&CollisionDamage>,
+    armor_query: Query<&MissileArmor>,
+    missile_query: Query<(), With<SpaceshipMissile>>,
+    mut collision_damage_event_writer: EventWriter<CollisionDamageEvent>,
 ) {
     for &event in collision_event_reader.read() {
         let CollisionEvent::Started(entity1, entity2, _) = event else { continue };
 
-        let Ok(mut health) = health_query.get_mut(entity1) else { continue };
-        let Ok(collision_damage) = collision_damage_query.get(entity2) else { continue };
-        health.value -= collision_damage.amount;
-
-        let Ok(mut health) = health_query.get_mut(entity2) else { continue };
-        let Ok(collision_damage) = collision_damage_query.get(entity1) else { continue };
-        health.value -= collision_damage.amount;
+        // Either side may lack health, e.g. hazards that only deal damage
+        for (target, source) in [(entity1, entity2), (entity2, entity1)] {
+            let Ok(mut health) = health_query.get_mut(target) else { continue };
+            let Ok(collision_damage) = collision_damage_query.get(source) else { continue };
+            let amount = match armor_query.get(target) {
+                Ok(&MissileArmor(fraction)) if missile_query.contains(source) => collision_damage.amount * fraction,
+                _ => collision_damage.amount,
+            };
+            health.value -= amount;
+            collision_damage_event_writer.send(CollisionDamageEvent { entity: target, amount });
+        }
     }
 }
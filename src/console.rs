@@ -0,0 +1,319 @@
+use bevy::prelude::*;
+
+#[cfg(feature = "dev-tools")]
+pub use overlay::ConsolePlugin;
+
+const MAX_LOG_LINES: usize = 14;
+
+
+/// Words after the command name, as typed
+pub type ConsoleArgs = Vec<String>;
+
+
+/// Output of the console, command handlers report back through it
+#[derive(Resource, Default, Debug)]
+pub struct ConsoleLog {
+    lines: Vec<String>,
+}
+
+
+impl ConsoleLog {
+    pub fn info(&mut self, line: impl Into<String>) {
+        self.lines.push(line.into());
+        if self.lines.len() > MAX_LOG_LINES {
+            self.lines.remove(0);
+        }
+    }
+
+    pub fn error(&mut self, line: impl Into<String>) {
+        self.info(format!("error: {}", line.into()));
+    }
+}
+
+
+/// Lets every plugin register its own console commands, which are dropped unless `dev-tools` is on
+pub trait ConsoleCommandsExt {
+    fn add_console_command<M>(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        completions: &'static [&'static str],
+        system: impl IntoSystem<ConsoleArgs, (), M> + 'static,
+    ) -> &mut Self;
+}
+
+
+impl ConsoleCommandsExt for App {
+    #[cfg(feature = "dev-tools")]
+    fn add_console_command<M>(
+        &mut self,
+        name: &'static str,
+        help: &'static str,
+        completions: &'static [&'static str],
+        system: impl IntoSystem<ConsoleArgs, (), M> + 'static,
+    ) -> &mut Self {
+        overlay::register(self, name, help, completions, system);
+        self
+    }
+
+    #[cfg(not(feature = "dev-tools"))]
+    fn add_console_command<M>(
+        &mut self,
+        _name: &'static str,
+        _help: &'static str,
+        _completions: &'static [&'static str],
+        _system: impl IntoSystem<ConsoleArgs, (), M> + 'static,
+    ) -> &mut Self {
+        self
+    }
+}
+
+
+/// Parses the argument at `index`, reporting a missing or malformed value to the console
+pub fn parse_arg<T: std::str::FromStr>(args: &ConsoleArgs, index: usize, name: &str, log: &mut ConsoleLog) -> Option<T> {
+    let Some(arg) = args.get(index) else {
+        log.error(format!("missing <{}>", name));
+        return None;
+    };
+    let value = arg.parse().ok();
+    if value.is_none() {
+        log.error(format!("invalid <{}>: {}", name, arg));
+    }
+    value
+}
+
+
+// Overlay and input handling, only compiled in with the `dev-tools` feature
+#[cfg(feature = "dev-tools")]
+mod overlay {
+    use std::collections::BTreeMap;
+
+    use bevy::{
+        ecs::system::SystemId,
+        input::{keyboard::KeyboardInput, InputSystem},
+        prelude::*,
+        window::ReceivedCharacter,
+    };
+
+    use super::{parse_arg, ConsoleArgs, ConsoleCommandsExt, ConsoleLog};
+
+    const TOGGLE_KEY: KeyCode = KeyCode::Backquote;
+    const FONT_SIZE: f32 = 18.;
+    const TEXT_COLOR: Color = Color::rgb(0.85, 0.95, 0.85);
+    const BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.8);
+
+
+    struct ConsoleCommand {
+        help: &'static str,
+        /// Suggestions for the first argument
+        completions: &'static [&'static str],
+        system: SystemId<ConsoleArgs>,
+    }
+
+
+    // Sorted by name, so help and completions come out in a stable order
+    #[derive(Resource, Default)]
+    struct ConsoleCommands(BTreeMap<&'static str, ConsoleCommand>);
+
+
+    #[derive(Resource, Default, Debug)]
+    pub struct ConsoleState {
+        pub open: bool,
+        input: String,
+    }
+
+
+    #[derive(Component, Debug)]
+    struct ConsoleRoot;
+
+
+    #[derive(Component, Debug)]
+    struct ConsoleText;
+
+
+    pub(super) fn register<M>(
+        app: &mut App,
+        name: &'static str,
+        help: &'static str,
+        completions: &'static [&'static str],
+        system: impl IntoSystem<ConsoleArgs, (), M> + 'static,
+    ) {
+        let system = app.world.register_system(system);
+        app.world.get_resource_or_insert_with(ConsoleCommands::default).0
+            .insert(name, ConsoleCommand { help, completions, system });
+    }
+
+
+    pub struct ConsolePlugin;
+
+
+    impl Plugin for ConsolePlugin {
+        fn build(&self, app: &mut App) {
+            app
+                .init_resource::<ConsoleCommands>()
+                .init_resource::<ConsoleLog>()
+                .init_resource::<ConsoleState>()
+                .add_console_command("help", "help - lists every command", &[], help_command)
+                .add_console_command("timescale", "timescale <f> - speeds up or slows down the game", &[], timescale_command)
+                .add_systems(Startup, setup_console)
+                // Swallows the keyboard while open, so typing doesn't steer the ship
+                .add_systems(PreUpdate, console_input.after(InputSystem))
+                .add_systems(Update, update_console_text.run_if(resource_changed::<ConsoleState>.or_else(resource_changed::<ConsoleLog>)));
+        }
+    }
+
+
+    fn setup_console(mut commands: Commands) {
+        commands.spawn((
+            NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    width: Val::Percent(100.0),
+                    padding: UiRect::all(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: BACKGROUND_COLOR.into(),
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            ConsoleRoot,
+        )).with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", TextStyle {
+                    font_size: FONT_SIZE,
+                    color: TEXT_COLOR,
+                    ..default()
+                }),
+                ConsoleText,
+            ));
+        });
+    }
+
+
+    fn console_input(
+        mut commands: Commands,
+        mut state: ResMut<ConsoleState>,
+        mut log: ResMut<ConsoleLog>,
+        console_commands: Res<ConsoleCommands>,
+        mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+        mut keyboard_events: ResMut<Events<KeyboardInput>>,
+        mut character_events: EventReader<ReceivedCharacter>,
+    ) {
+        if keyboard_input.just_pressed(TOGGLE_KEY) {
+            state.open = !state.open;
+            character_events.clear();
+            keyboard_input.reset_all();
+            return;
+        }
+        if !state.open {
+            character_events.clear();
+            return;
+        }
+
+        for event in character_events.read() {
+            for character in event.char.chars() {
+                if !character.is_control() {
+                    state.input.push(character);
+                }
+            }
+        }
+        if keyboard_input.just_pressed(KeyCode::Backspace) {
+            state.input.pop();
+        }
+        if keyboard_input.just_pressed(KeyCode::Escape) {
+            state.open = false;
+        }
+        if keyboard_input.just_pressed(KeyCode::Tab) {
+            let completed = complete(&state.input, &console_commands, &mut log);
+            state.input = completed;
+        }
+        if keyboard_input.just_pressed(KeyCode::Enter) {
+            let line = std::mem::take(&mut state.input);
+            run_command(&mut commands, &line, &console_commands, &mut log);
+        }
+        keyboard_input.reset_all();
+        keyboard_events.clear();
+    }
+
+
+    fn run_command(commands: &mut Commands, line: &str, console_commands: &ConsoleCommands, log: &mut ConsoleLog) {
+        let mut words = line.split_whitespace().map(str::to_string);
+        let Some(name) = words.next() else { return };
+        log.info(format!("> {}", line.trim()));
+        match console_commands.0.get(name.as_str()) {
+            Some(command) => commands.run_system_with_input(command.system, words.collect()),
+            None => log.error(format!("unknown command '{}', try 'help'", name)),
+        }
+    }
+
+
+    // Completes the command name, or its first argument, to the longest common prefix of the candidates
+    fn complete(input: &str, console_commands: &ConsoleCommands, log: &mut ConsoleLog) -> String {
+        let words: Vec<&str> = input.split_whitespace().collect();
+        let completing_new_word = input.is_empty() || input.ends_with(' ');
+        let (prefix, candidates): (&str, Vec<&str>) = match (words.as_slice(), completing_new_word) {
+            ([], _) => ("", console_commands.0.keys().copied().collect()),
+            ([name], false) => (*name, console_commands.0.keys().copied().collect()),
+            ([name], true) | ([name, _], false) => {
+                let Some(command) = console_commands.0.get(*name) else { return input.to_string() };
+                (if completing_new_word { "" } else { words[1] }, command.completions.to_vec())
+            },
+            _ => return input.to_string(),
+        };
+        let matches: Vec<&str> = candidates.into_iter().filter(|candidate| candidate.starts_with(prefix)).collect();
+        let Some(first) = matches.first() else { return input.to_string() };
+        if matches.len() > 1 {
+            log.info(matches.join("  "));
+        }
+        let common = matches.iter().fold(first.to_string(), |common, candidate| {
+            common.chars().zip(candidate.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a).collect()
+        });
+        let head = &input[..input.len() - prefix.len()];
+        let suffix = if matches.len() == 1 { " " } else { "" };
+        format!("{}{}{}", head, common, suffix)
+    }
+
+
+    fn update_console_text(
+        state: Res<ConsoleState>,
+        log: Res<ConsoleLog>,
+        mut root_query: Query<&mut Style, With<ConsoleRoot>>,
+        mut text_query: Query<&mut Text, With<ConsoleText>>,
+    ) {
+        if let Ok(mut style) = root_query.get_single_mut() {
+            style.display = if state.open { Display::Flex } else { Display::None };
+        }
+        let Ok(mut text) = text_query.get_single_mut() else { return };
+        if text.sections.is_empty() {
+            return;
+        }
+        let mut value = log.lines.join("\n");
+        if !value.is_empty() {
+            value.push('\n');
+        }
+        value.push_str(&format!("> {}_", state.input));
+        text.sections[0].value = value;
+    }
+
+
+    fn help_command(In(_): In<ConsoleArgs>, console_commands: Res<ConsoleCommands>, mut log: ResMut<ConsoleLog>) {
+        for command in console_commands.0.values() {
+            log.info(command.help);
+        }
+    }
+
+
+    fn timescale_command(In(args): In<ConsoleArgs>, mut time: ResMut<Time<Virtual>>, mut log: ResMut<ConsoleLog>) {
+        let Some(scale) = parse_arg::<f32>(&args, 0, "f", &mut log) else { return };
+        // `set_relative_speed` panics on anything else
+        if !scale.is_finite() || scale <= 0. {
+            log.error("timescale has to be a positive number");
+            return;
+        }
+        time.set_relative_speed(scale);
+        log.info(format!("timescale set to {}", scale));
+    }
+}
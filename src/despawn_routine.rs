@@ -3,21 +3,37 @@ use bevy::prelude::*;
 use crate::schedule::InGameSet;
 use crate::state::GameState;
 use crate::health::Health;
+use crate::pool::{EntityPool, Idle, Pooled};
+use crate::camera::GameCamera;
 
-const DESPAWN_DISTANCE: f32 = 100.;
+// Extra room around the camera view, so nothing pops out right at the screen edge
+const VIEW_MARGIN: f32 = 20.;
+const ARENA_HALF_EXTENT: f32 = 250.;
 
 
 #[derive(Component, Debug)]
 pub struct DisposableEntity;
 
 
+/// When an entity gets culled for leaving the playing field, disposable entities default to `OffScreen`
+#[derive(Component, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DespawnPolicy {
+    /// Outside of the camera view on the play plane
+    #[default]
+    OffScreen,
+    /// Outside of the arena around the world origin
+    OutsideArena,
+    Never,
+}
+
+
 pub struct DespawnPlugin;
 
 
 impl Plugin for DespawnPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(Update, (
+            .add_systems(FixedUpdate, (
                 despawn_far_away_entities,
                 despawn_dead_entities,
             ).in_set(InGameSet::DespawnEntities))
@@ -26,37 +42,87 @@ impl Plugin for DespawnPlugin {
 }
 
 
-fn despawn_far_away_entities(mut commands: Commands, query: Query<(Entity, &GlobalTransform), With<DisposableEntity>>) {
-    for (entity, transform) in query.iter() {
-        let distance = transform.translation().distance(Vec3::ZERO);
-
-        // Entity is far away from the camera's viewport.
-        if distance > DESPAWN_DISTANCE {
-            if let Some(entity_commands) = commands.get_entity(entity) {
-                entity_commands.despawn_recursive();
-            }
+fn despawn_far_away_entities(
+    mut commands: Commands,
+    query: Query<
+        (Entity, &GlobalTransform, Option<&DespawnPolicy>, Option<&Pooled>),
+        (Or<(With<DisposableEntity>, With<DespawnPolicy>)>, Without<Idle>),
+    >,
+    camera_query: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    mut pool: ResMut<EntityPool>,
+) {
+    let view = camera_query.get_single().ok()
+        .and_then(|(camera, camera_transform)| view_bounds(camera, camera_transform))
+        .map(|view| view.inflate(VIEW_MARGIN));
+    let arena = Rect::from_center_half_size(Vec2::ZERO, Vec2::splat(ARENA_HALF_EXTENT));
+
+    for (entity, transform, policy, pooled) in query.iter() {
+        let translation = transform.translation();
+        let position = Vec2::new(translation.x, translation.z);
+        let outside = match policy.copied().unwrap_or_default() {
+            DespawnPolicy::OffScreen => view.map_or(false, |view| !view.contains(position)),
+            DespawnPolicy::OutsideArena => !arena.contains(position),
+            DespawnPolicy::Never => false,
+        };
+        if outside {
+            dispose(&mut commands, &mut pool, entity, pooled);
         }
     }
 }
 
 
-fn despawn_dead_entities(mut commands: Commands, query: Query<(Entity, &Health), (With<Health>, With<DisposableEntity>)>) {
-    for (entity, health) in query.iter() {
+// Area of the play plane (XZ, as x and y of the rect) seen by the camera
+fn view_bounds(camera: &Camera, camera_transform: &GlobalTransform) -> Option<Rect> {
+    let size = camera.logical_viewport_size()?;
+    let mut bounds: Option<Rect> = None;
+    for corner in [Vec2::ZERO, Vec2::new(size.x, 0.), Vec2::new(0., size.y), size] {
+        let ray = camera.viewport_to_world(camera_transform, corner)?;
+        let distance = ray.intersect_plane(Vec3::ZERO, Plane3d::new(Vec3::Y))?;
+        let point = ray.get_point(distance);
+        let point = Vec2::new(point.x, point.z);
+        bounds = Some(bounds.map_or(Rect::from_corners(point, point), |bounds| bounds.union_point(point)));
+    }
+    bounds
+}
+
+
+fn despawn_dead_entities(
+    mut commands: Commands,
+    query: Query<(Entity, &Health, Option<&Pooled>), (With<DisposableEntity>, Without<Idle>)>,
+    mut pool: ResMut<EntityPool>,
+) {
+    for (entity, health, pooled) in query.iter() {
         if health.value > 0. {
             continue;
         }
+        dispose(&mut commands, &mut pool, entity, pooled);
+    }
+}
 
-        if let Some(entity_commands) = commands.get_entity(entity) {
-            entity_commands.despawn_recursive();
-        }
+
+// Pooled entities are parked for reuse, everything else is despawned
+pub fn dispose(commands: &mut Commands, pool: &mut EntityPool, entity: Entity, pooled: Option<&Pooled>) {
+    match pooled {
+        Some(&Pooled(kind)) => pool.release(commands, entity, kind),
+        None => {
+            if let Some(entity_commands) = commands.get_entity(entity) {
+                entity_commands.despawn_recursive();
+            }
+        },
     }
 }
 
 
-fn despawn_all_entities(mut commands: Commands, query: Query<Entity, Or<(With<Health>, With<DisposableEntity>)>>) {
+fn despawn_all_entities(
+    mut commands: Commands,
+    // Children, like boss parts, go along with their parent
+    query: Query<Entity, (Or<(With<Health>, With<DisposableEntity>)>, Without<Parent>)>,
+    mut pool: ResMut<EntityPool>,
+) {
     for entity in query.iter() {
         if let Some(entity_commands) = commands.get_entity(entity) {
             entity_commands.despawn_recursive();
         }
     }
+    pool.clear();
 }
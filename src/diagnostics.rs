@@ -0,0 +1,322 @@
+use std::sync::Mutex;
+
+use bevy::{
+    app::AppExit,
+    diagnostic::{
+        Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, EntityCountDiagnosticsPlugin,
+        FrameTimeDiagnosticsPlugin, RegisterDiagnostic,
+    },
+    log::{tracing_subscriber::{prelude::*, EnvFilter, Registry}, BoxedSubscriber, LogPlugin},
+    prelude::*,
+    utils::{HashMap, Instant},
+};
+use bevy_rapier3d::prelude::*;
+use tracing_chrome::{ChromeLayerBuilder, FlushGuard};
+
+use crate::schedule::InGameSet;
+use crate::state::GameState;
+use crate::asteroids::{Asteroid, AsteroidParticle};
+use crate::spaceship::SpaceshipMissile;
+use crate::pool::Idle;
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+const TRACE_CHROME_FLAG: &'static str = "--trace-chrome";
+const DEFAULT_TRACE_PATH: &'static str = "trace.json";
+const LOG_FILTER: &'static str = "info,wgpu=error,naga=warn";
+
+const FONT_SIZE: f32 = 16.;
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const BACKGROUND_COLOR: Color = Color::rgba(0., 0., 0., 0.6);
+
+const GRAPH_SAMPLES: usize = 60;
+const GRAPH_BAR_WIDTH: f32 = 3.;
+const GRAPH_HEIGHT: f32 = 50.;
+// Frame time at the top of the graph, and the thresholds of a good and a bad frame
+const GRAPH_MAX_MS: f64 = 50.;
+const GOOD_FRAME_MS: f64 = 1000. / 60.;
+const BAD_FRAME_MS: f64 = 1000. / 30.;
+const GOOD_FRAME_COLOR: Color = Color::rgb(0.3, 0.9, 0.4);
+const SLOW_FRAME_COLOR: Color = Color::rgb(0.95, 0.8, 0.2);
+const BAD_FRAME_COLOR: Color = Color::rgb(0.95, 0.3, 0.25);
+
+const TIMED_SETS: [InGameSet; 4] = [
+    InGameSet::DespawnEntities,
+    InGameSet::UserInput,
+    InGameSet::EntityUpdates,
+    InGameSet::CollisionDetection,
+];
+
+const ASTEROID_COUNT: DiagnosticPath = DiagnosticPath::const_new("game/asteroids");
+const PARTICLE_COUNT: DiagnosticPath = DiagnosticPath::const_new("game/asteroid_particles");
+const MISSILE_COUNT: DiagnosticPath = DiagnosticPath::const_new("game/missiles");
+const RAPIER_BODY_COUNT: DiagnosticPath = DiagnosticPath::const_new("rapier/bodies");
+const RAPIER_COLLIDER_COUNT: DiagnosticPath = DiagnosticPath::const_new("rapier/colliders");
+
+// Keeps the trace file open until the app exits, dropping it flushes the file
+static TRACE_GUARD: Mutex<Option<FlushGuard>> = Mutex::new(None);
+
+
+/// Log configuration, adding a Chrome trace layer when the game is started with `--trace-chrome [path]`
+pub fn log_plugin() -> LogPlugin {
+    LogPlugin {
+        update_subscriber: trace_chrome_path().map(|_| trace_chrome_subscriber as fn(BoxedSubscriber) -> BoxedSubscriber),
+        ..default()
+    }
+}
+
+
+fn trace_chrome_path() -> Option<String> {
+    let mut args = std::env::args().skip_while(|arg| arg != TRACE_CHROME_FLAG);
+    args.next()?;
+    Some(args.next().filter(|path| !path.starts_with("--")).unwrap_or_else(|| DEFAULT_TRACE_PATH.to_string()))
+}
+
+
+// The chrome layer has to look spans up, which the boxed subscriber can't do, so the stack is rebuilt around it.
+// Per-system spans are only emitted with the `trace` feature
+fn trace_chrome_subscriber(_: BoxedSubscriber) -> BoxedSubscriber {
+    let path = trace_chrome_path().unwrap_or_else(|| DEFAULT_TRACE_PATH.to_string());
+    let (chrome_layer, guard) = ChromeLayerBuilder::new()
+        .file(&path)
+        .include_args(true)
+        .build();
+    *TRACE_GUARD.lock().unwrap() = Some(guard);
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(LOG_FILTER));
+    Box::new(Registry::default()
+        .with(filter)
+        .with(bevy::log::tracing_subscriber::fmt::layer())
+        .with(chrome_layer))
+}
+
+
+#[derive(Resource, Default, Debug)]
+struct OverlayVisible(bool);
+
+
+// When each timed set started this tick
+#[derive(Resource, Default, Debug)]
+struct SetTimings {
+    started: HashMap<InGameSet, Instant>,
+}
+
+
+#[derive(Component, Debug)]
+struct DiagnosticsOverlay;
+
+
+#[derive(Component, Debug)]
+struct DiagnosticsText;
+
+
+// Bar of the frame time graph, 0 being the oldest sample
+#[derive(Component, Debug)]
+struct FrameGraphBar(usize);
+
+
+pub struct DiagnosticsOverlayPlugin;
+
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_plugins((FrameTimeDiagnosticsPlugin, EntityCountDiagnosticsPlugin))
+            .init_resource::<OverlayVisible>()
+            .init_resource::<SetTimings>()
+            .register_diagnostic(Diagnostic::new(ASTEROID_COUNT))
+            .register_diagnostic(Diagnostic::new(PARTICLE_COUNT))
+            .register_diagnostic(Diagnostic::new(MISSILE_COUNT))
+            .register_diagnostic(Diagnostic::new(RAPIER_BODY_COUNT))
+            .register_diagnostic(Diagnostic::new(RAPIER_COLLIDER_COUNT))
+            .add_systems(Startup, setup_overlay)
+            .add_systems(Update, (
+                toggle_overlay,
+                measure_entity_counts,
+                update_overlay.run_if(|visible: Res<OverlayVisible>| visible.0),
+            ).chain())
+            .add_systems(Last, flush_trace_on_exit);
+
+        // Each timing starts after the set before it and ends before the next one,
+        // so the other sets and the Rapier step after the last one aren't counted in
+        for (index, set) in TIMED_SETS.iter().enumerate() {
+            let path = set_diagnostic_path(set);
+            let mut begin = begin_set_timing(set.clone()).before(set.clone());
+            if let Some(previous) = index.checked_sub(1).map(|previous| TIMED_SETS[previous].clone()) {
+                begin = begin.after(previous);
+            }
+            let end = end_set_timing(set.clone(), path.clone()).after(set.clone());
+            let end = match TIMED_SETS.get(index + 1) {
+                Some(next) => end.before(next.clone()),
+                None => end.before(PhysicsSet::SyncBackend),
+            };
+            app
+                .register_diagnostic(Diagnostic::new(path).with_suffix("ms"))
+                .add_systems(FixedUpdate, (begin, end).run_if(in_state(GameState::InGame)));
+        }
+    }
+}
+
+
+fn set_diagnostic_path(set: &InGameSet) -> DiagnosticPath {
+    DiagnosticPath::new(format!("in_game_set/{:?}", set))
+}
+
+
+fn begin_set_timing(set: InGameSet) -> impl FnMut(ResMut<SetTimings>) {
+    move |mut timings| {
+        timings.started.insert(set.clone(), Instant::now());
+    }
+}
+
+
+// Wall time between the set's first and last system, systems running in parallel with it are counted in
+fn end_set_timing(set: InGameSet, path: DiagnosticPath) -> impl FnMut(ResMut<SetTimings>, Diagnostics) {
+    move |mut timings, mut diagnostics| {
+        let Some(started) = timings.started.remove(&set) else { return };
+        diagnostics.add_measurement(&path, || started.elapsed().as_secs_f64() * 1000.);
+    }
+}
+
+
+fn setup_overlay(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                left: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(6.0)),
+                row_gap: Val::Px(6.0),
+                ..default()
+            },
+            background_color: BACKGROUND_COLOR.into(),
+            z_index: ZIndex::Global(50),
+            ..default()
+        },
+        DiagnosticsOverlay,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: FONT_SIZE,
+                color: TEXT_COLOR,
+                ..default()
+            }),
+            DiagnosticsText,
+        ));
+        parent.spawn(NodeBundle {
+            style: Style {
+                height: Val::Px(GRAPH_HEIGHT),
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+            ..default()
+        }).with_children(|parent| {
+            for index in 0..GRAPH_SAMPLES {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Px(GRAPH_BAR_WIDTH),
+                            height: Val::Px(0.0),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    FrameGraphBar(index),
+                ));
+            }
+        });
+    });
+}
+
+
+fn toggle_overlay(
+    mut visible: ResMut<OverlayVisible>,
+    mut query: Query<&mut Style, With<DiagnosticsOverlay>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+) {
+    if !keyboard_input.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+    visible.0 = !visible.0;
+    let Ok(mut style) = query.get_single_mut() else { return };
+    style.display = if visible.0 { Display::Flex } else { Display::None };
+}
+
+
+fn measure_entity_counts(
+    mut diagnostics: Diagnostics,
+    asteroid_query: Query<(), With<Asteroid>>,
+    particle_query: Query<(), (With<AsteroidParticle>, Without<Idle>)>,
+    missile_query: Query<(), (With<SpaceshipMissile>, Without<Idle>)>,
+    rapier_context: Res<RapierContext>,
+) {
+    diagnostics.add_measurement(&ASTEROID_COUNT, || asteroid_query.iter().count() as f64);
+    diagnostics.add_measurement(&PARTICLE_COUNT, || particle_query.iter().count() as f64);
+    diagnostics.add_measurement(&MISSILE_COUNT, || missile_query.iter().count() as f64);
+    diagnostics.add_measurement(&RAPIER_BODY_COUNT, || rapier_context.bodies.len() as f64);
+    diagnostics.add_measurement(&RAPIER_COLLIDER_COUNT, || rapier_context.colliders.len() as f64);
+}
+
+
+fn update_overlay(
+    store: Res<DiagnosticsStore>,
+    mut text_query: Query<&mut Text, With<DiagnosticsText>>,
+    mut bar_query: Query<(&FrameGraphBar, &mut Style, &mut BackgroundColor)>,
+) {
+    let smoothed = |path: &DiagnosticPath| store.get(path).and_then(|diagnostic| diagnostic.smoothed());
+    let value = |path: &DiagnosticPath| store.get(path).and_then(|diagnostic| diagnostic.value()).unwrap_or(0.);
+
+    if let Ok(mut text) = text_query.get_single_mut() {
+        let mut lines = vec![
+            format!(
+                "FPS {:.0}  frame {:.2} ms",
+                smoothed(&FrameTimeDiagnosticsPlugin::FPS).unwrap_or(0.),
+                smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME).unwrap_or(0.),
+            ),
+            format!(
+                "entities {}  asteroids {}  particles {}  missiles {}",
+                value(&EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+                value(&ASTEROID_COUNT),
+                value(&PARTICLE_COUNT),
+                value(&MISSILE_COUNT),
+            ),
+            format!("rapier bodies {}  colliders {}", value(&RAPIER_BODY_COUNT), value(&RAPIER_COLLIDER_COUNT)),
+        ];
+        for set in TIMED_SETS {
+            let path = set_diagnostic_path(&set);
+            lines.push(match smoothed(&path) {
+                Some(ms) => format!("{:?} {:.3} ms", set, ms),
+                None => format!("{:?} -", set),
+            });
+        }
+        if let Some(section) = text.sections.first_mut() {
+            section.value = lines.join("\n");
+        }
+    }
+
+    // Newest sample on the right
+    let Some(frame_time) = store.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME) else { return };
+    let samples: Vec<f64> = frame_time.values().copied().collect();
+    let offset = GRAPH_SAMPLES.saturating_sub(samples.len());
+    let first = samples.len().saturating_sub(GRAPH_SAMPLES);
+    for (&FrameGraphBar(index), mut style, mut color) in bar_query.iter_mut() {
+        let Some(&ms) = index.checked_sub(offset).and_then(|index| samples.get(first + index)) else {
+            style.height = Val::Px(0.0);
+            continue;
+        };
+        style.height = Val::Px((ms.min(GRAPH_MAX_MS) / GRAPH_MAX_MS) as f32 * GRAPH_HEIGHT);
+        *color = match ms {
+            ms if ms <= GOOD_FRAME_MS => GOOD_FRAME_COLOR,
+            ms if ms <= BAD_FRAME_MS => SLOW_FRAME_COLOR,
+            _ => BAD_FRAME_COLOR,
+        }.into();
+    }
+}
+
+
+fn flush_trace_on_exit(mut app_exit_event_reader: EventReader<AppExit>) {
+    if app_exit_event_reader.read().next().is_some() {
+        TRACE_GUARD.lock().unwrap().take();
+    }
+}
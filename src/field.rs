@@ -1,4 +1,19 @@
-use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    transform::TransformSystem,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::state::GameState;
+use crate::settings::Settings;
+use crate::camera::GameCamera;
 
 const STARTING_BG_COLOR: Color = Color::rgb(0.1, 0., 0.15);
 
@@ -6,6 +21,50 @@ const STARTING_LIGHT_COLOR: Color = Color::rgb(1., 1., 1.);
 
 const STARTING_LIGHT_BRIGHTNESS: f32 = 500.;
 
+// (depth below the play plane, star count, star size, parallax follow factor)
+// Far layers follow the camera more closely, so they appear to move slower
+const STAR_LAYERS: &'static [(f32, usize, f32, f32)] = &[
+    (100., 500, 0.35, 0.3),
+    (250., 700, 0.6, 0.6),
+    (500., 900, 1.0, 0.85),
+];
+const STAR_FIELD_MARGIN: f32 = 150.;
+
+const NEBULA_COUNT: usize = 3;
+const NEBULA_DEPTH: f32 = 400.;
+const NEBULA_SIZE: (f32, f32) = (200., 450.);
+const NEBULA_PARALLAX: f32 = 0.75;
+const NEBULA_TEXTURE_SIZE: u32 = 64;
+
+const MAX_PLANETS: usize = 2;
+const PLANET_DEPTH: f32 = 300.;
+const PLANET_RADIUS: (f32, f32) = (15., 45.);
+const PLANET_PARALLAX: f32 = 0.7;
+
+
+/// Seed of the generated backdrop, a new one is rolled for every run
+#[derive(Resource, Debug)]
+pub struct BackgroundSeed(pub u64);
+
+
+impl Default for BackgroundSeed {
+    fn default() -> Self {
+        Self(rand::random())
+    }
+}
+
+
+#[derive(Component, Debug)]
+pub struct BackgroundItem;
+
+
+// Follows the camera by `factor`, 0 is fixed in the world and 1 is glued to the screen
+#[derive(Component, Debug)]
+pub struct ParallaxLayer {
+    factor: f32,
+    origin: Vec3,
+}
+
 
 pub struct FieldPlugin;
 
@@ -17,6 +76,180 @@ impl Plugin for FieldPlugin {
             .insert_resource(AmbientLight {
                 color: STARTING_LIGHT_COLOR,
                 brightness: STARTING_LIGHT_BRIGHTNESS,
-            });
+            })
+            .init_resource::<BackgroundSeed>()
+            .add_systems(OnEnter(GameState::GameOver), reroll_background_seed)
+            // The density check goes first, so it sees every value
+            .add_systems(Update, spawn_background.run_if(
+                background_density_changed.or_else(resource_changed::<BackgroundSeed>)
+            ))
+            .add_systems(PostUpdate, update_parallax_layers.before(TransformSystem::TransformPropagate));
+    }
+}
+
+
+fn reroll_background_seed(mut seed: ResMut<BackgroundSeed>) {
+    *seed = BackgroundSeed::default();
+}
+
+
+// Only the density, not every other setting, is worth regenerating the whole background for
+fn background_density_changed(settings: Res<Settings>, mut applied: Local<Option<f32>>) -> bool {
+    let changed = *applied != Some(settings.background_density);
+    *applied = Some(settings.background_density);
+    changed
+}
+
+
+fn spawn_background(
+    mut commands: Commands,
+    query: Query<Entity, With<BackgroundItem>>,
+    seed: Res<BackgroundSeed>,
+    settings: Res<Settings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let mut rng = StdRng::seed_from_u64(seed.0);
+
+    // Every star layer is a single mesh of small quads, tinted with vertex colors
+    let star_material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        unlit: true,
+        cull_mode: None,
+        ..default()
+    });
+    for &(depth, count, size, factor) in STAR_LAYERS {
+        let count = (count as f32 * settings.background_density) as usize;
+        let extent = visible_half_extent(depth) + STAR_FIELD_MARGIN;
+        let origin = Vec3::new(0., -depth, 0.);
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(star_layer_mesh(&mut rng, count, extent, size)),
+                material: star_material.clone(),
+                transform: Transform::from_translation(origin),
+                ..default()
+            },
+            ParallaxLayer { factor, origin },
+            BackgroundItem,
+        ));
+    }
+
+    for _ in 0..NEBULA_COUNT {
+        let hue = rng.gen_range(0.0..360.0);
+        let size = rng.gen_range(NEBULA_SIZE.0..NEBULA_SIZE.1);
+        let extent = visible_half_extent(NEBULA_DEPTH);
+        let texture = images.add(nebula_image(&mut rng));
+        let origin = Vec3::new(rng.gen_range(-extent..extent), -NEBULA_DEPTH, rng.gen_range(-extent..extent));
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(Plane3d::default().mesh().size(size, size)),
+                material: materials.add(StandardMaterial {
+                    base_color: Color::hsla(hue, 0.6, 0.5, 0.35),
+                    base_color_texture: Some(texture),
+                    unlit: true,
+                    alpha_mode: AlphaMode::Blend,
+                    ..default()
+                }),
+                transform: Transform::from_translation(origin)
+                    .with_rotation(Quat::from_rotation_y(rng.gen_range(0.0..TAU))),
+                ..default()
+            },
+            ParallaxLayer { factor: NEBULA_PARALLAX, origin },
+            BackgroundItem,
+        ));
+    }
+
+    let planet_count = (rng.gen_range(0..=MAX_PLANETS) as f32 * settings.background_density).round() as usize;
+    for _ in 0..planet_count {
+        let radius = rng.gen_range(PLANET_RADIUS.0..PLANET_RADIUS.1);
+        let extent = visible_half_extent(PLANET_DEPTH) * 0.8;
+        let origin = Vec3::new(rng.gen_range(-extent..extent), -PLANET_DEPTH, rng.gen_range(-extent..extent));
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(Sphere::new(radius).mesh().uv(32, 18)),
+                material: materials.add(StandardMaterial {
+                    base_color: Color::hsl(rng.gen_range(0.0..360.0), 0.4, 0.35),
+                    perceptual_roughness: 1.,
+                    ..default()
+                }),
+                transform: Transform::from_translation(origin),
+                ..default()
+            },
+            ParallaxLayer { factor: PLANET_PARALLAX, origin },
+            BackgroundItem,
+        ));
+    }
+}
+
+
+// Rough half-size of the area seen by the top-down camera at a given depth
+fn visible_half_extent(depth: f32) -> f32 {
+    depth * 0.75
+}
+
+
+fn star_layer_mesh(rng: &mut StdRng, count: usize, extent: f32, size: f32) -> Mesh {
+    let mut positions = Vec::with_capacity(count * 4);
+    let mut colors = Vec::with_capacity(count * 4);
+    let mut indices = Vec::with_capacity(count * 6);
+    for i in 0..count as u32 {
+        let center = Vec3::new(rng.gen_range(-extent..extent), 0., rng.gen_range(-extent..extent));
+        let half = size * rng.gen_range(0.5..1.5) / 2.;
+        let brightness: f32 = rng.gen_range(0.4..1.);
+        // Slightly blue or yellow tint
+        let tint: f32 = rng.gen_range(-0.15..0.15);
+        let color = [brightness * (1. + tint), brightness, brightness * (1. - tint), 1.];
+        for offset in [Vec3::new(-half, 0., -half), Vec3::new(half, 0., -half), Vec3::new(half, 0., half), Vec3::new(-half, 0., half)] {
+            positions.push((center + offset).to_array());
+            colors.push(color);
+        }
+        let base = i * 4;
+        indices.extend_from_slice(&[base, base + 2, base + 1, base, base + 3, base + 2]);
+    }
+    let normals = vec![[0f32, 1., 0.]; positions.len()];
+
+    Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+        .with_inserted_indices(Indices::U32(indices))
+}
+
+
+// Soft radial blob with some noise, tinted by the material color
+fn nebula_image(rng: &mut StdRng) -> Image {
+    let size = NEBULA_TEXTURE_SIZE;
+    let center = (size as f32 - 1.) / 2.;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let distance = Vec2::new(x as f32 - center, y as f32 - center).length() / center;
+            let falloff = (1. - distance).clamp(0., 1.).powi(2);
+            let alpha = (falloff * rng.gen_range(0.7..1.) * 255.) as u8;
+            data.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+    Image::new(
+        Extent3d { width: size, height: size, depth_or_array_layers: 1 },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+
+fn update_parallax_layers(
+    mut query: Query<(&mut Transform, &ParallaxLayer)>,
+    camera_query: Query<&GameCamera>,
+) {
+    let Ok(camera) = camera_query.get_single() else { return };
+    let focus = camera.focus();
+    for (mut transform, layer) in query.iter_mut() {
+        transform.translation = layer.origin + Vec3::new(focus.x, 0., focus.z) * layer.factor;
     }
 }
@@ -0,0 +1,355 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::schedule::InGameSet;
+use crate::state::GameState;
+use crate::asset_loader::{LoadingAssets, RonAssetLoader};
+use crate::health::Health;
+use crate::collision_detection::{apply_collision_damage, CollisionDamage};
+use crate::despawn_routine::{DespawnPolicy, DisposableEntity};
+use crate::pool::Idle;
+use crate::spaceship::Spaceship;
+use crate::camera::CameraShakeEvent;
+use crate::particles::{ParticleBurstEvent, ParticleEffectKind};
+use crate::console::{parse_arg, ConsoleArgs, ConsoleCommandsExt, ConsoleLog};
+
+const HAZARDS_PATH: &'static str = "game.hazards.ron";
+
+const PLANET_COLOR: Color = Color::rgb(0.35, 0.45, 0.6);
+const PLANET_COLLISION_DAMAGE: f32 = 50.;
+const BLACK_HOLE_COLOR: Color = Color::rgb(0.02, 0.02, 0.03);
+// Anything falling into a black hole is gone
+const BLACK_HOLE_COLLISION_DAMAGE: f32 = 100_000.;
+
+const ION_STORM_COLOR: Color = Color::rgba(0.3, 0.5, 1., 0.25);
+const ION_STORM_HEIGHT: f32 = 0.5;
+
+const MINE_RADIUS: f32 = 0.8;
+const MINE_COLOR: Color = Color::rgb(1., 0.2, 0.1);
+const MINE_COLLISION_DAMAGE: f32 = 45.;
+const MINE_TRAUMA: f32 = 0.4;
+
+// Used by the console command, which only gets a kind and a position
+const DEFAULT_WELL: (f32, f32, f32) = (4., 35., 3000.);
+const DEFAULT_ION_STORM_RADIUS: f32 = 15.;
+const DEFAULT_MINE_FIELD: (f32, u32) = (10., 6);
+
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum HazardKind {
+    /// Gravity well with a solid core that bodies bounce off
+    Planet { core_radius: f32, pull_radius: f32, strength: f32 },
+    /// Gravity well destroying whatever reaches its core
+    BlackHole { core_radius: f32, pull_radius: f32, strength: f32 },
+    /// Area in which the ship's weapons don't work
+    IonStorm { radius: f32 },
+    MineField { radius: f32, count: u32 },
+}
+
+
+impl HazardKind {
+    const NAMES: [&'static str; 4] = ["planet", "black_hole", "ion_storm", "mine_field"];
+
+    // Kind with default dimensions, by its console name
+    fn parse(name: &str) -> Option<Self> {
+        let (core_radius, pull_radius, strength) = DEFAULT_WELL;
+        match name {
+            "planet" => Some(Self::Planet { core_radius, pull_radius, strength }),
+            "black_hole" => Some(Self::BlackHole { core_radius, pull_radius, strength }),
+            "ion_storm" => Some(Self::IonStorm { radius: DEFAULT_ION_STORM_RADIUS }),
+            "mine_field" => Some(Self::MineField { radius: DEFAULT_MINE_FIELD.0, count: DEFAULT_MINE_FIELD.1 }),
+            _ => None,
+        }
+    }
+}
+
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub struct HazardPlacement {
+    /// Position on the play plane (x, z)
+    pub position: (f32, f32),
+    pub kind: HazardKind,
+}
+
+
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct HazardLayout {
+    pub hazards: Vec<HazardPlacement>,
+}
+
+
+#[derive(Resource, Default, Debug)]
+pub struct HazardAssets {
+    pub layout: Handle<HazardLayout>,
+    pub sphere_mesh: Handle<Mesh>,
+    pub storm_mesh: Handle<Mesh>,
+    pub planet_material: Handle<StandardMaterial>,
+    pub black_hole_material: Handle<StandardMaterial>,
+    pub storm_material: Handle<StandardMaterial>,
+    pub mine_material: Handle<StandardMaterial>,
+}
+
+
+/// Pulls dynamic bodies within `pull_radius` towards its center
+#[derive(Component, Clone, Copy, Debug)]
+pub struct GravityWell {
+    pub core_radius: f32,
+    pub pull_radius: f32,
+    pub strength: f32,
+}
+
+
+impl GravityWell {
+    /// Acceleration of a body at the given offset from the well's center
+    fn acceleration(&self, offset: Vec3) -> Vec3 {
+        let offset = Vec3::new(offset.x, 0., offset.z);
+        let distance = offset.length();
+        if distance > self.pull_radius || distance == 0. {
+            return Vec3::ZERO;
+        }
+        // Capped at the core, so bodies grazing it don't get flung away
+        let falloff = distance.max(self.core_radius).powi(2);
+        -offset / distance * self.strength / falloff
+    }
+}
+
+
+#[derive(Component, Debug)]
+pub struct IonStorm;
+
+
+/// Ship inside an ion storm, its weapons don't fire
+#[derive(Component, Debug)]
+pub struct WeaponsDisabled;
+
+
+#[derive(Component, Debug)]
+pub struct Mine;
+
+
+pub struct HazardPlugin;
+
+
+impl Plugin for HazardPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<HazardAssets>()
+            .init_asset::<HazardLayout>()
+            .register_asset_loader(RonAssetLoader::<HazardLayout>::new(&["hazards.ron"]))
+            .add_console_command(
+                "hazard",
+                "hazard <kind> <x> <z> - places a hazard on the play plane",
+                &HazardKind::NAMES,
+                hazard_command,
+            )
+            .add_systems(Startup, load_hazard_assets)
+            .add_systems(OnExit(GameState::Menu), spawn_hazards)
+            .add_systems(OnEnter(GameState::GameOver), spawn_hazards)
+            .add_systems(FixedUpdate, (
+                apply_gravity,
+                disable_weapons_in_ion_storms,
+            ).in_set(InGameSet::EntityUpdates))
+            .add_systems(FixedUpdate, explode_mines
+                .after(apply_collision_damage)
+                .in_set(InGameSet::CollisionDetection));
+    }
+}
+
+
+fn load_hazard_assets(
+    mut hazard_assets: ResMut<HazardAssets>,
+    mut loading_assets: ResMut<LoadingAssets>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    *hazard_assets = HazardAssets {
+        layout: asset_server.load(HAZARDS_PATH),
+        // Unit sized, scaled to each hazard
+        sphere_mesh: meshes.add(Sphere::new(1.).mesh().uv(32, 18)),
+        storm_mesh: meshes.add(Cylinder::new(1., ION_STORM_HEIGHT)),
+        planet_material: materials.add(PLANET_COLOR),
+        black_hole_material: materials.add(StandardMaterial {
+            base_color: BLACK_HOLE_COLOR,
+            unlit: true,
+            ..default()
+        }),
+        storm_material: materials.add(StandardMaterial {
+            base_color: ION_STORM_COLOR,
+            emissive: ION_STORM_COLOR,
+            alpha_mode: AlphaMode::Blend,
+            ..default()
+        }),
+        mine_material: materials.add(StandardMaterial {
+            base_color: MINE_COLOR,
+            emissive: MINE_COLOR,
+            ..default()
+        }),
+    };
+    loading_assets.track(&hazard_assets.layout);
+}
+
+
+fn spawn_hazards(
+    mut commands: Commands,
+    hazard_assets: Res<HazardAssets>,
+    layouts: Res<Assets<HazardLayout>>,
+) {
+    let Some(layout) = layouts.get(&hazard_assets.layout) else { return };
+    for &HazardPlacement { position, kind } in layout.hazards.iter() {
+        spawn_hazard(&mut commands, &hazard_assets, kind, Vec3::new(position.0, 0., position.1));
+    }
+}
+
+
+fn spawn_hazard(commands: &mut Commands, hazard_assets: &HazardAssets, kind: HazardKind, translation: Vec3) {
+    // Cleared with everything else when the run ends
+    let cleanup = (DisposableEntity, DespawnPolicy::Never);
+    match kind {
+        HazardKind::Planet { core_radius, pull_radius, strength } | HazardKind::BlackHole { core_radius, pull_radius, strength } => {
+            let black_hole = matches!(kind, HazardKind::BlackHole { .. });
+            let mut well = commands.spawn((
+                PbrBundle {
+                    mesh: hazard_assets.sphere_mesh.clone(),
+                    material: if black_hole { hazard_assets.black_hole_material.clone() } else { hazard_assets.planet_material.clone() },
+                    transform: Transform::from_translation(translation).with_scale(Vec3::splat(core_radius)),
+                    ..default()
+                },
+                // A unit ball, scaled along with the mesh
+                Collider::ball(1.),
+                ActiveEvents::COLLISION_EVENTS,
+                // The ship is kinematic and the well has no body
+                ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+                GravityWell { core_radius, pull_radius, strength },
+                CollisionDamage::new(if black_hole { BLACK_HOLE_COLLISION_DAMAGE } else { PLANET_COLLISION_DAMAGE }),
+                cleanup,
+            ));
+            if black_hole {
+                well.insert(Sensor);
+            }
+        },
+        HazardKind::IonStorm { radius } => {
+            commands.spawn((
+                PbrBundle {
+                    mesh: hazard_assets.storm_mesh.clone(),
+                    material: hazard_assets.storm_material.clone(),
+                    transform: Transform::from_translation(translation).with_scale(Vec3::new(radius, 1., radius)),
+                    ..default()
+                },
+                Collider::cylinder(ION_STORM_HEIGHT / 2., 1.),
+                Sensor,
+                ActiveCollisionTypes::all(),
+                IonStorm,
+                cleanup,
+            ));
+        },
+        HazardKind::MineField { radius, count } => {
+            let mut rng = rand::thread_rng();
+            for _ in 0..count {
+                // Uniform over the disc
+                let angle = rng.gen_range(0.0..TAU);
+                let distance = radius * rng.gen_range(0.0f32..1.).sqrt();
+                let position = translation + Quat::from_rotation_y(angle) * Vec3::Z * distance;
+                commands.spawn((
+                    PbrBundle {
+                        mesh: hazard_assets.sphere_mesh.clone(),
+                        material: hazard_assets.mine_material.clone(),
+                        transform: Transform::from_translation(position).with_scale(Vec3::splat(MINE_RADIUS)),
+                        ..default()
+                    },
+                    Collider::ball(1.),
+                    Sensor,
+                    ActiveEvents::COLLISION_EVENTS,
+                    ActiveCollisionTypes::default() | ActiveCollisionTypes::KINEMATIC_STATIC,
+                    Mine,
+                    // Goes off at the first touch, missiles included
+                    Health::new(1.),
+                    CollisionDamage::new(MINE_COLLISION_DAMAGE),
+                    cleanup,
+                ));
+            }
+        },
+    }
+}
+
+
+// Forces are set from scratch every tick, so bodies leaving a well stop feeling it
+fn apply_gravity(
+    well_query: Query<(&GravityWell, &GlobalTransform)>,
+    mut body_query: Query<(&RigidBody, &GlobalTransform, &ReadMassProperties, &mut ExternalForce), Without<Idle>>,
+) {
+    let wells: Vec<(&GravityWell, Vec3)> = well_query.iter()
+        .map(|(well, transform)| (well, transform.translation()))
+        .collect();
+    for (&body, transform, mass_properties, mut external_force) in body_query.iter_mut() {
+        if body != RigidBody::Dynamic {
+            continue;
+        }
+        let translation = transform.translation();
+        let acceleration: Vec3 = wells.iter()
+            .map(|&(well, center)| well.acceleration(translation - center))
+            .sum();
+        let force = acceleration * mass_properties.get().mass;
+        if external_force.force != force {
+            external_force.force = force;
+        }
+    }
+}
+
+
+fn disable_weapons_in_ion_storms(
+    mut commands: Commands,
+    storm_query: Query<Entity, With<IonStorm>>,
+    ship_query: Query<(Entity, Has<WeaponsDisabled>), With<Spaceship>>,
+    rapier_context: Res<RapierContext>,
+) {
+    let Ok((ship, disabled)) = ship_query.get_single() else { return };
+    let inside = storm_query.iter().any(|storm| rapier_context.intersection_pair(storm, ship) == Some(true));
+    if inside && !disabled {
+        commands.entity(ship).insert(WeaponsDisabled);
+    } else if !inside && disabled {
+        commands.entity(ship).remove::<WeaponsDisabled>();
+    }
+}
+
+
+// Despawning is left to the despawn routine, like any other dead entity
+fn explode_mines(
+    query: Query<(&Health, &GlobalTransform), With<Mine>>,
+    mut camera_shake_event_writer: EventWriter<CameraShakeEvent>,
+    mut particle_burst_event_writer: EventWriter<ParticleBurstEvent>,
+) {
+    for (health, transform) in query.iter() {
+        if health.value > 0. {
+            continue;
+        }
+        let translation = transform.translation();
+        camera_shake_event_writer.send(CameraShakeEvent { trauma: MINE_TRAUMA, origin: Some(translation) });
+        particle_burst_event_writer.send(ParticleBurstEvent {
+            kind: ParticleEffectKind::AsteroidExplosion,
+            translation,
+            direction: Vec3::ZERO,
+        });
+    }
+}
+
+
+fn hazard_command(
+    In(args): In<ConsoleArgs>,
+    mut commands: Commands,
+    hazard_assets: Res<HazardAssets>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let Some(kind) = args.first().and_then(|name| HazardKind::parse(name)) else {
+        log.error(format!("usage: hazard <{}> <x> <z>", HazardKind::NAMES.join("|")));
+        return;
+    };
+    let Some(x) = parse_arg::<f32>(&args, 1, "x", &mut log) else { return };
+    let Some(z) = parse_arg::<f32>(&args, 2, "z", &mut log) else { return };
+    spawn_hazard(&mut commands, &hazard_assets, kind, Vec3::new(x, 0., z));
+    log.info(format!("placed {} at ({}, {})", args[0], x, z));
+}
@@ -10,3 +10,8 @@ pub struct Health {
 impl Health {
     pub fn new(value: f32) -> Self { Self { value } }
 }
+
+
+/// Takes no collision damage, used by the `god` console command
+#[derive(Component, Debug)]
+pub struct Invulnerable;
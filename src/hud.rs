@@ -1,13 +1,45 @@
 use bevy::prelude::*;
+use bevy_rapier3d::prelude::Velocity;
 
-use crate::schedule::InGameSet;
 use crate::state::GameState;
+use crate::interpolation::interpolate_transforms;
 use crate::health::Health;
 use crate::spaceship::Spaceship;
+use crate::settings::Settings;
+use crate::accessibility::{Accessibility, Palette};
+use crate::scoring::Combo;
+use crate::stats::AchievementUnlockedEvent;
+use crate::pool::Idle;
+use crate::camera::GameCamera;
+use crate::console::{ConsoleArgs, ConsoleCommandsExt, ConsoleLog};
+use crate::locale::Locale;
+use crate::boss::Boss;
 
 const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
 const TEXT_FONT_SIZE: f32 = 40.;
 
+const RADAR_SIZE: f32 = 180.;
+const RADAR_BLIP_SIZE: f32 = 6.;
+const RADAR_BACKGROUND_COLOR: Color = Color::rgba(0.05, 0.15, 0.1, 0.6);
+
+const BOSS_BAR_WIDTH: f32 = 40.;
+const BOSS_BAR_HEIGHT: f32 = 14.;
+const BOSS_BAR_FONT_SIZE: f32 = 24.;
+const BOSS_BAR_BACKGROUND_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.7);
+
+const THREAT_EDGE_MARGIN: f32 = 30.;
+// Arrow size for threats at the near and far distance
+const THREAT_ARROW_SIZE: (f32, f32) = (40., 16.);
+const THREAT_DISTANCE: (f32, f32) = (30., 150.);
+// Seconds until impact below which a threat is shown as critical or high
+const THREAT_CRITICAL_SECONDS: f32 = 2.;
+const THREAT_HIGH_SECONDS: f32 = 5.;
+
+const TOAST_SECONDS: f32 = 4.;
+const TOAST_BACKGROUND_COLOR: Color = Color::rgba(0.1, 0.1, 0.15, 0.85);
+const TOAST_TITLE_FONT_SIZE: f32 = 26.;
+const TOAST_TEXT_FONT_SIZE: f32 = 18.;
+
 
 #[derive(Component, Default, Debug)]
 pub struct GameScore {
@@ -26,6 +58,18 @@ pub struct GameScoreChangeEvent {
 pub struct HudItem;
 
 
+// HUD text whose font size follows the HUD scale setting
+#[derive(Component, Default, Debug)]
+pub struct ScaledHudText {
+    pub font_size: f32,
+}
+
+
+impl ScaledHudText {
+    pub fn new(font_size: f32) -> Self { Self { font_size } }
+}
+
+
 #[derive(Component, Default, Debug)]
 pub struct HpHudItem;
 
@@ -34,6 +78,75 @@ pub struct HpHudItem;
 pub struct ScoreHudItem;
 
 
+#[derive(Component, Default, Debug)]
+pub struct ComboHudItem;
+
+
+/// Entity shown on the radar, the variant picks the blip color from the palette
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RadarBlip {
+    Asteroid,
+    Enemy,
+    Pickup,
+}
+
+
+impl RadarBlip {
+    fn color(self, palette: &Palette) -> Color {
+        match self {
+            Self::Asteroid => palette.asteroid,
+            Self::Enemy => palette.enemy,
+            Self::Pickup => palette.pickup,
+        }
+    }
+}
+
+
+#[derive(Component, Default, Debug)]
+pub struct RadarHudItem;
+
+
+// Reused blip nodes of the radar, the unused ones are hidden
+#[derive(Component, Default, Debug)]
+pub struct RadarBlipNode;
+
+
+// Boss name and health bar under the text row, hidden while there is no boss
+#[derive(Component, Default, Debug)]
+pub struct BossBarHudItem;
+
+
+#[derive(Component, Default, Debug)]
+pub struct BossBarText;
+
+
+#[derive(Component, Default, Debug)]
+pub struct BossBarFill;
+
+
+// Full screen layer holding the off-screen threat arrows
+#[derive(Component, Default, Debug)]
+pub struct ThreatIndicatorLayer;
+
+
+// Reused arrow nodes pointing at off-screen threats, pointing right before being rotated
+#[derive(Component, Default, Debug)]
+pub struct ThreatIndicator;
+
+
+// Column in the top right corner holding the achievement toasts
+#[derive(Component, Default, Debug)]
+pub struct ToastContainer;
+
+
+// Keeps its own timer rather than a `Lifetime`, which only ticks in game,
+// since achievements also unlock on the game over screen
+#[derive(Component, Debug)]
+pub struct AchievementToast {
+    timer: Timer,
+}
+
+
 pub struct HudPlugin;
 
 
@@ -41,11 +154,18 @@ impl Plugin for HudPlugin {
     fn build(&self, app: &mut App) {
         app
             .add_event::<GameScoreChangeEvent>()
-            .add_systems(Startup, setup_score)
+            .add_console_command("score", "score +<n> - adds to the score, or takes away with -<n>", &[], score_command)
+            .add_systems(Startup, (setup_score, setup_toast_container))
+            .add_systems(Update, (spawn_achievement_toasts, expire_achievement_toasts))
             .add_systems(Update, (
                 (update_game_score, update_game_score_in_hud).chain(),
                 update_hp_in_hud,
-            ).in_set(InGameSet::EntityUpdates))
+                update_combo_in_hud,
+                update_radar,
+                update_threat_indicators,
+                update_boss_bar,
+            ).after(interpolate_transforms).run_if(in_state(GameState::InGame)))
+            .add_systems(Update, (apply_hud_scale, apply_radar_scale).run_if(resource_changed::<Settings>))
             .add_systems(OnEnter(GameState::GameOver), clear_game_score)
             .add_systems(OnExit(GameState::Menu), setup_hud);
     }
@@ -57,7 +177,26 @@ fn setup_score(mut commands: Commands) {
 }
 
 
-fn setup_hud(mut commands: Commands) {
+fn setup_toast_container(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexEnd,
+                ..default()
+            },
+            z_index: ZIndex::Global(10),
+            ..default()
+        },
+        ToastContainer,
+    ));
+}
+
+
+fn setup_hud(mut commands: Commands, settings: Res<Settings>, accessibility: Res<Accessibility>) {
     commands.spawn((
         NodeBundle {
             style: Style {
@@ -72,42 +211,474 @@ fn setup_hud(mut commands: Commands) {
         HudItem,
     )).with_children(|parent| {
         parent.spawn((
-            TextBundle::from_section("HP: 0", TextStyle {
-                font_size: TEXT_FONT_SIZE,
+            TextBundle::from_section("", TextStyle {
+                font_size: TEXT_FONT_SIZE * settings.hud_scale,
                 color: TEXT_COLOR,
                 ..default()
             }),
+            ScaledHudText::new(TEXT_FONT_SIZE),
             HpHudItem,
         ));
         parent.spawn((
-            TextBundle::from_section("Score: 0", TextStyle {
-                font_size: TEXT_FONT_SIZE,
+            TextBundle::from_section("", TextStyle {
+                font_size: TEXT_FONT_SIZE * settings.hud_scale,
                 color: TEXT_COLOR,
                 ..default()
             }),
+            ScaledHudText::new(TEXT_FONT_SIZE),
             ScoreHudItem,
         ));
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: TEXT_FONT_SIZE * settings.hud_scale,
+                color: TEXT_COLOR,
+                ..default()
+            }),
+            ScaledHudText::new(TEXT_FONT_SIZE),
+            ComboHudItem,
+        ));
+        parent.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(0.0),
+                    top: Val::Px(0.0),
+                    width: Val::Vw(100.0),
+                    height: Val::Vh(100.0),
+                    ..default()
+                },
+                ..default()
+            },
+            ThreatIndicatorLayer,
+        ));
+        // Radar in the top left corner, right below the text row
+        parent.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(100.0),
+                    left: Val::Px(20.0),
+                    width: Val::Px(RADAR_SIZE * settings.radar_scale),
+                    height: Val::Px(RADAR_SIZE * settings.radar_scale),
+                    ..default()
+                },
+                background_color: RADAR_BACKGROUND_COLOR.into(),
+                ..default()
+            },
+            RadarHudItem,
+        )).with_children(|parent| {
+            parent.spawn(NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(50.0),
+                    top: Val::Percent(50.0),
+                    width: Val::Px(RADAR_BLIP_SIZE * 1.5),
+                    height: Val::Px(RADAR_BLIP_SIZE * 1.5),
+                    margin: UiRect::all(Val::Px(-RADAR_BLIP_SIZE * 0.75)),
+                    ..default()
+                },
+                background_color: accessibility.colors().ship.into(),
+                ..default()
+            });
+        });
+        parent.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(100.0),
+                    left: Val::Percent((100. - BOSS_BAR_WIDTH) / 2.),
+                    width: Val::Percent(BOSS_BAR_WIDTH),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            BossBarHudItem,
+        )).with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", TextStyle {
+                    font_size: BOSS_BAR_FONT_SIZE * settings.hud_scale,
+                    color: TEXT_COLOR,
+                    ..default()
+                }),
+                ScaledHudText::new(BOSS_BAR_FONT_SIZE),
+                BossBarText,
+            ));
+            parent.spawn(NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(BOSS_BAR_HEIGHT),
+                    ..default()
+                },
+                background_color: BOSS_BAR_BACKGROUND_COLOR.into(),
+                ..default()
+            }).with_children(|parent| {
+                parent.spawn((
+                    NodeBundle {
+                        style: Style {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: accessibility.colors().enemy.into(),
+                        ..default()
+                    },
+                    BossBarFill,
+                ));
+            });
+        });
     });
 }
 
 
-fn update_hp_in_hud(mut query: Query<&mut Text, With<HpHudItem>>, health_query: Query<&Health, With<Spaceship>>) {
+fn apply_hud_scale(mut query: Query<(&mut Text, &ScaledHudText)>, settings: Res<Settings>) {
+    for (mut text, scaled_text) in query.iter_mut() {
+        for section in text.sections.iter_mut() {
+            section.style.font_size = scaled_text.font_size * settings.hud_scale;
+        }
+    }
+}
+
+
+fn apply_radar_scale(mut query: Query<&mut Style, With<RadarHudItem>>, settings: Res<Settings>) {
+    for mut style in query.iter_mut() {
+        style.width = Val::Px(RADAR_SIZE * settings.radar_scale);
+        style.height = Val::Px(RADAR_SIZE * settings.radar_scale);
+    }
+}
+
+
+// Plots everything within range relative to the ship, with the ship's heading pointing up
+fn update_radar(
+    mut commands: Commands,
+    radar_query: Query<(Entity, Option<&Children>), With<RadarHudItem>>,
+    mut blip_query: Query<(&mut Style, &mut BackgroundColor, &mut Visibility), With<RadarBlipNode>>,
+    ship_query: Query<&Transform, With<Spaceship>>,
+    target_query: Query<(&GlobalTransform, &RadarBlip), Without<Idle>>,
+    settings: Res<Settings>,
+    accessibility: Res<Accessibility>,
+) {
+    let Ok((radar, children)) = radar_query.get_single() else { return };
+    let palette = accessibility.colors();
+    let ship = ship_query.get_single().ok();
+    let blip_nodes: Vec<Entity> = children
+        .map(|children| children.iter().copied().filter(|&child| blip_query.contains(child)).collect())
+        .unwrap_or_default();
+    let mut blips = blip_nodes.into_iter();
+
+    if let Some(ship) = ship {
+        // Only the heading matters, rolling the ship doesn't tilt the radar
+        let heading = ship.rotation * Vec3::Z;
+        let to_ship_space = Quat::from_rotation_y(heading.x.atan2(heading.z)).inverse();
+        for (transform, &blip) in target_query.iter() {
+            let offset = transform.translation() - ship.translation;
+            let offset = Vec3::new(offset.x, 0., offset.z);
+            if offset.length() > settings.radar_range {
+                continue;
+            }
+            // The ship flies along its local +Z and the camera shows world -X on the right
+            let local = to_ship_space * offset / settings.radar_range;
+            let left = Val::Percent(50. * (1. - local.x));
+            let top = Val::Percent(50. * (1. - local.z));
+            match blips.next().and_then(|node| blip_query.get_mut(node).ok()) {
+                Some((mut style, mut color, mut visibility)) => {
+                    style.left = left;
+                    style.top = top;
+                    *color = blip.color(&palette).into();
+                    *visibility = Visibility::Inherited;
+                },
+                // Shown from the next frame on
+                None => {
+                    let node = commands.spawn((
+                        NodeBundle {
+                            style: Style {
+                                position_type: PositionType::Absolute,
+                                left,
+                                top,
+                                width: Val::Px(RADAR_BLIP_SIZE),
+                                height: Val::Px(RADAR_BLIP_SIZE),
+                                margin: UiRect::all(Val::Px(-RADAR_BLIP_SIZE / 2.)),
+                                ..default()
+                            },
+                            background_color: blip.color(&palette).into(),
+                            ..default()
+                        },
+                        RadarBlipNode,
+                    )).id();
+                    commands.entity(radar).add_child(node);
+                },
+            }
+        }
+    }
+    for node in blips {
+        if let Ok((_, _, mut visibility)) = blip_query.get_mut(node) {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+
+// Arrows along the screen border towards off-screen asteroids and enemies closing in on the ship
+fn update_threat_indicators(
+    mut commands: Commands,
+    layer_query: Query<(Entity, Option<&Children>), With<ThreatIndicatorLayer>>,
+    mut indicator_query: Query<(&mut Style, &mut Transform, &mut Visibility, &Children), With<ThreatIndicator>>,
+    mut color_query: Query<&mut BackgroundColor>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+    ship_query: Query<&Transform, (With<Spaceship>, Without<ThreatIndicator>)>,
+    threat_query: Query<(&GlobalTransform, &Velocity, &RadarBlip), Without<Idle>>,
+    accessibility: Res<Accessibility>,
+) {
+    let palette = accessibility.colors();
+    let Ok((layer, children)) = layer_query.get_single() else { return };
+    let indicator_nodes: Vec<Entity> = children
+        .map(|children| children.iter().copied().filter(|&child| indicator_query.contains(child)).collect())
+        .unwrap_or_default();
+    let mut indicators = indicator_nodes.into_iter();
+
+    let view = camera_query.get_single().ok().zip(ship_query.get_single().ok());
+    if let Some(((camera, camera_transform), ship)) = view {
+        let viewport = camera.logical_viewport_size().unwrap_or_default();
+        let center = viewport / 2.;
+        let half_extent = (center - Vec2::splat(THREAT_EDGE_MARGIN)).max(Vec2::ONE);
+
+        for (transform, velocity, &blip) in threat_query.iter() {
+            if blip == RadarBlip::Pickup {
+                continue;
+            }
+            let translation = transform.translation();
+            let to_ship = ship.translation - translation;
+            let distance = to_ship.length();
+            let closing_speed = velocity.linvel.dot(to_ship.normalize_or_zero());
+            if closing_speed <= 0. {
+                continue;  // Not heading towards the ship
+            }
+            let Some(position) = camera.world_to_viewport(camera_transform, translation) else { continue };
+            let offset = position - center;
+            if offset.x.abs() <= center.x && offset.y.abs() <= center.y {
+                continue;  // Already on screen
+            }
+
+            // Pin the arrow where the line from the screen center to the threat crosses the border
+            let direction = offset.normalize_or_zero();
+            let reach = (half_extent.x / direction.x.abs()).min(half_extent.y / direction.y.abs());
+            let anchor = center + direction * reach;
+            let t = ((distance - THREAT_DISTANCE.0) / (THREAT_DISTANCE.1 - THREAT_DISTANCE.0)).clamp(0., 1.);
+            let size = THREAT_ARROW_SIZE.0 + (THREAT_ARROW_SIZE.1 - THREAT_ARROW_SIZE.0) * t;
+            let seconds_to_impact = distance / closing_speed;
+            let color = if seconds_to_impact < THREAT_CRITICAL_SECONDS {
+                palette.threat_critical
+            } else if seconds_to_impact < THREAT_HIGH_SECONDS {
+                palette.threat_high
+            } else {
+                palette.threat_low
+            };
+            let style = Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(anchor.x - size / 2.),
+                top: Val::Px(anchor.y - size / 2.),
+                width: Val::Px(size),
+                height: Val::Px(size),
+                ..default()
+            };
+            // UI space points down, so this turns the arrow clockwise on screen
+            let rotation = Quat::from_rotation_z(direction.y.atan2(direction.x));
+
+            match indicators.next().and_then(|node| indicator_query.get_mut(node).ok()) {
+                Some((mut node_style, mut node_transform, mut visibility, parts)) => {
+                    *node_style = style;
+                    node_transform.rotation = rotation;
+                    *visibility = Visibility::Inherited;
+                    for &part in parts.iter() {
+                        if let Ok(mut part_color) = color_query.get_mut(part) {
+                            *part_color = color.into();
+                        }
+                    }
+                },
+                // Shown from the next frame on
+                None => {
+                    let node = spawn_threat_indicator(&mut commands, style, rotation, color);
+                    commands.entity(layer).add_child(node);
+                },
+            }
+        }
+    }
+    for node in indicators {
+        if let Ok((_, _, mut visibility, _)) = indicator_query.get_mut(node) {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+
+// A shaft with a diamond head at its right end
+fn spawn_threat_indicator(commands: &mut Commands, style: Style, rotation: Quat, color: Color) -> Entity {
+    commands.spawn((
+        NodeBundle {
+            style,
+            transform: Transform::from_rotation(rotation),
+            ..default()
+        },
+        ThreatIndicator,
+    )).with_children(|parent| {
+        parent.spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(0.0),
+                top: Val::Percent(42.0),
+                width: Val::Percent(70.0),
+                height: Val::Percent(16.0),
+                ..default()
+            },
+            background_color: color.into(),
+            ..default()
+        });
+        parent.spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                left: Val::Percent(55.0),
+                top: Val::Percent(30.0),
+                width: Val::Percent(40.0),
+                height: Val::Percent(40.0),
+                ..default()
+            },
+            background_color: color.into(),
+            transform: Transform::from_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+            ..default()
+        });
+    }).id()
+}
+
+
+fn update_hp_in_hud(
+    mut query: Query<&mut Text, With<HpHudItem>>,
+    health_query: Query<&Health, With<Spaceship>>,
+    locale: Res<Locale>,
+) {
     let Ok(health) = health_query.get_single() else { return };
     let Ok(mut hp_hud_item) = query.get_single_mut() else { return };
     if hp_hud_item.sections.is_empty() {
         return;
     }
-    hp_hud_item.sections[0].value = format!("HP: {}", health.value as i32);
+    hp_hud_item.sections[0].value = locale.format("hud-hp", &[("hp", locale.group_digits(health.value as i64).into())]);
 }
 
 
-fn update_game_score_in_hud(mut query: Query<&mut Text, With<ScoreHudItem>>, score_query: Query<&GameScore>) {
+fn update_game_score_in_hud(
+    mut query: Query<&mut Text, With<ScoreHudItem>>,
+    score_query: Query<&GameScore>,
+    locale: Res<Locale>,
+) {
     let Ok(score) = score_query.get_single() else { return };
     let Ok(mut score_hud_item) = query.get_single_mut() else { return };
     if score_hud_item.sections.is_empty() {
         return;
     }
-    score_hud_item.sections[0].value = format!("Score: {}", score.value);
+    score_hud_item.sections[0].value = locale.format("hud-score", &[("score", locale.group_digits(score.value as i64).into())]);
+}
+
+
+// Multiplier and the time left to keep the combo going, hidden while there is no combo
+fn update_combo_in_hud(mut query: Query<&mut Text, With<ComboHudItem>>, combo: Res<Combo>, locale: Res<Locale>) {
+    let Ok(mut combo_hud_item) = query.get_single_mut() else { return };
+    if combo_hud_item.sections.is_empty() {
+        return;
+    }
+    combo_hud_item.sections[0].value = if combo.multiplier > 1. {
+        locale.format("hud-combo", &[
+            ("multiplier", locale.decimal(combo.multiplier, 2).into()),
+            ("seconds", locale.decimal(combo.timer.remaining_secs(), 1).into()),
+        ])
+    } else {
+        String::new()
+    };
+}
+
+
+fn update_boss_bar(
+    mut bar_query: Query<&mut Visibility, With<BossBarHudItem>>,
+    mut text_query: Query<&mut Text, With<BossBarText>>,
+    mut fill_query: Query<&mut Style, With<BossBarFill>>,
+    boss_query: Query<&Boss>,
+    locale: Res<Locale>,
+) {
+    let Ok(mut visibility) = bar_query.get_single_mut() else { return };
+    let Some(boss) = boss_query.iter().next() else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+    visibility.set_if_neq(Visibility::Inherited);
+    if let Ok(mut text) = text_query.get_single_mut() {
+        let (phase, phases) = boss.phase_progress();
+        if let Some(section) = text.sections.first_mut() {
+            section.value = locale.format("hud-boss", &[
+                ("name", locale.text(boss.name_key()).into()),
+                ("phase", (phase as i64).into()),
+                ("phases", (phases as i64).into()),
+            ]);
+        }
+    }
+    if let Ok(mut style) = fill_query.get_single_mut() {
+        style.width = Val::Percent(boss.health_fraction() * 100.);
+    }
+}
+
+
+fn spawn_achievement_toasts(
+    mut commands: Commands,
+    mut achievement_unlocked_event_reader: EventReader<AchievementUnlockedEvent>,
+    query: Query<Entity, With<ToastContainer>>,
+    settings: Res<Settings>,
+    locale: Res<Locale>,
+) {
+    let Ok(container) = query.get_single() else { return };
+    for event in achievement_unlocked_event_reader.read() {
+        let toast = commands.spawn((
+            NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(10.0)),
+                    margin: UiRect::bottom(Val::Px(8.0)),
+                    ..default()
+                },
+                background_color: TOAST_BACKGROUND_COLOR.into(),
+                ..default()
+            },
+            AchievementToast { timer: Timer::from_seconds(TOAST_SECONDS, TimerMode::Once) },
+        )).with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(locale.format("hud-achievement-unlocked", &[("name", locale.text(&event.name_key).into())]), TextStyle {
+                    font_size: TOAST_TITLE_FONT_SIZE * settings.hud_scale,
+                    color: TEXT_COLOR,
+                    ..default()
+                }),
+                ScaledHudText::new(TOAST_TITLE_FONT_SIZE),
+            ));
+            parent.spawn((
+                TextBundle::from_section(locale.text(&event.description_key), TextStyle {
+                    font_size: TOAST_TEXT_FONT_SIZE * settings.hud_scale,
+                    color: TEXT_COLOR,
+                    ..default()
+                }),
+                ScaledHudText::new(TOAST_TEXT_FONT_SIZE),
+            ));
+        }).id();
+        commands.entity(container).add_child(toast);
+    }
+}
+
+
+fn expire_achievement_toasts(mut commands: Commands, mut query: Query<(Entity, &mut AchievementToast)>, time: Res<Time>) {
+    for (entity, mut toast) in query.iter_mut() {
+        if !toast.timer.tick(time.delta()).finished() {
+            continue;
+        }
+        if let Some(entity_commands) = commands.get_entity(entity) {
+            entity_commands.despawn_recursive();
+        }
+    }
 }
 
 
@@ -131,3 +702,21 @@ fn clear_game_score(mut score_change_event_writer: EventWriter<GameScoreChangeEv
         clear_score: true,
     });
 }
+
+
+fn score_command(
+    In(args): In<ConsoleArgs>,
+    mut score_change_event_writer: EventWriter<GameScoreChangeEvent>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let Some(arg) = args.first() else {
+        log.error("missing <n>");
+        return;
+    };
+    let Ok(score_delta) = arg.trim_start_matches('+').parse::<i32>() else {
+        log.error(format!("invalid <n>: {}", arg));
+        return;
+    };
+    score_change_event_writer.send(GameScoreChangeEvent { score_delta, clear_score: false });
+    log.info(format!("score {:+}", score_delta));
+}
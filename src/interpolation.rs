@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+
+
+/// Smooths the `Transform` of an entity simulated in `FixedUpdate` over the frames between ticks.
+/// Fixed systems see the simulated pose, everything else sees the interpolated one.
+#[derive(Component, Default, Clone, Copy, Debug)]
+pub struct InterpolatedTransform {
+    // Poses at the start and the end of the last tick
+    previous: Option<Transform>,
+    current: Option<Transform>,
+    // Pose written for rendering, any other value was set outside the fixed loop
+    rendered: Option<Transform>,
+}
+
+
+impl InterpolatedTransform {
+    // Starts over from the given pose, e.g. after a teleport
+    fn snap(&mut self, transform: Transform) {
+        self.previous = Some(transform);
+        self.current = Some(transform);
+        self.rendered = None;
+    }
+}
+
+
+pub struct InterpolationPlugin;
+
+
+impl Plugin for InterpolationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(FixedFirst, restore_simulated_transforms)
+            .add_systems(FixedLast, record_simulated_transforms)
+            .add_systems(Update, interpolate_transforms);
+    }
+}
+
+
+// Puts the simulated pose back before the tick moves the entity on
+fn restore_simulated_transforms(mut query: Query<(&mut Transform, &mut InterpolatedTransform)>) {
+    for (mut transform, mut interpolation) in query.iter_mut() {
+        if interpolation.rendered == Some(*transform) {
+            if let Some(current) = interpolation.current {
+                transform.set_if_neq(current);
+            }
+        }
+        interpolation.previous = Some(*transform);
+        interpolation.rendered = None;
+    }
+}
+
+
+fn record_simulated_transforms(mut query: Query<(&Transform, &mut InterpolatedTransform)>) {
+    for (transform, mut interpolation) in query.iter_mut() {
+        // Entities that joined during the tick have nothing to interpolate from yet
+        interpolation.previous.get_or_insert(*transform);
+        interpolation.current = Some(*transform);
+    }
+}
+
+
+/// Blends between the last two ticks by the time left over in the fixed clock
+pub fn interpolate_transforms(
+    mut query: Query<(&mut Transform, &mut InterpolatedTransform)>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    let t = fixed_time.overstep_fraction();
+    for (mut transform, mut interpolation) in query.iter_mut() {
+        if interpolation.rendered.is_some_and(|rendered| rendered != *transform) {
+            interpolation.snap(*transform);
+            continue;
+        }
+        let (Some(previous), Some(current)) = (interpolation.previous, interpolation.current) else { continue };
+        let rendered = Transform {
+            translation: previous.translation.lerp(current.translation, t),
+            rotation: previous.rotation.slerp(current.rotation, t),
+            scale: previous.scale.lerp(current.scale, t),
+        };
+        *transform = rendered;
+        interpolation.rendered = Some(rendered);
+    }
+}
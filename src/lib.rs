@@ -0,0 +1,3 @@
+//! Parts of the game that don't depend on the rest of it, shared by the game binary and the benchmarks
+
+pub mod pool;
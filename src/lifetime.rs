@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+
+use crate::schedule::InGameSet;
+use crate::despawn_routine::dispose;
+use crate::pool::{EntityPool, Idle, Pooled};
+
+
+#[derive(Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LifetimeFade {
+    #[default]
+    None,
+    /// Fades the materials of the entity and its descendants out, giving them their own copies
+    Fade,
+    /// Scales the entity down to nothing
+    Shrink,
+}
+
+
+/// Disposes of the entity once the time runs out, sending a `LifetimeExpiredEvent`
+#[derive(Component, Debug)]
+pub struct Lifetime {
+    timer: Timer,
+    fade: LifetimeFade,
+    /// Last part of the life, as a fraction of it, spent fading out
+    fade_fraction: f32,
+    /// Scale of the entity when the fade-out started
+    fade_start: Option<Vec3>,
+}
+
+
+impl Lifetime {
+    pub fn new(seconds: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+            fade: LifetimeFade::None,
+            fade_fraction: 0.,
+            fade_start: None,
+        }
+    }
+
+    pub fn with_fade(mut self, fade: LifetimeFade, fade_fraction: f32) -> Self {
+        self.fade = fade;
+        self.fade_fraction = fade_fraction.clamp(0., 1.);
+        self
+    }
+
+    /// Part of the life already spent, from 0 to 1
+    pub fn elapsed_fraction(&self) -> f32 {
+        self.timer.fraction()
+    }
+
+    /// Goes from 1 down to 0 over the fade-out part of the life
+    fn fade_factor(&self) -> f32 {
+        let left = self.timer.fraction_remaining();
+        if self.fade_fraction > 0. { (left / self.fade_fraction).min(1.) } else { 1. }
+    }
+}
+
+
+#[derive(Event, Debug)]
+pub struct LifetimeExpiredEvent {
+    pub entity: Entity,
+    pub translation: Vec3,
+}
+
+
+pub struct LifetimePlugin;
+
+
+impl Plugin for LifetimePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_event::<LifetimeExpiredEvent>()
+            .add_systems(FixedUpdate, tick_lifetimes.in_set(InGameSet::EntityUpdates));
+    }
+}
+
+
+pub fn tick_lifetimes(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Lifetime, &mut Transform, &GlobalTransform, Option<&Pooled>), Without<Idle>>,
+    children_query: Query<&Children>,
+    material_query: Query<&Handle<StandardMaterial>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut pool: ResMut<EntityPool>,
+    mut lifetime_expired_event_writer: EventWriter<LifetimeExpiredEvent>,
+    time: Res<Time>,
+) {
+    for (entity, mut lifetime, mut transform, global_transform, pooled) in query.iter_mut() {
+        lifetime.timer.tick(time.delta());
+        if lifetime.timer.finished() {
+            lifetime_expired_event_writer.send(LifetimeExpiredEvent {
+                entity,
+                translation: global_transform.translation(),
+            });
+            dispose(&mut commands, &mut pool, entity, pooled);
+            continue;
+        }
+
+        let factor = lifetime.fade_factor();
+        if factor >= 1. {
+            continue;
+        }
+        // Remember the starting scale, and make the materials unique before touching them
+        let starting = lifetime.fade_start.is_none();
+        let start_scale = *lifetime.fade_start.get_or_insert(transform.scale);
+        match lifetime.fade {
+            LifetimeFade::None => (),
+            LifetimeFade::Shrink => transform.scale = start_scale * factor,
+            LifetimeFade::Fade => {
+                for target in std::iter::once(entity).chain(children_query.iter_descendants(entity)) {
+                    let Ok(handle) = material_query.get(target) else { continue };
+                    if starting {
+                        let Some(material) = materials.get(handle).cloned() else { continue };
+                        let handle = materials.add(StandardMaterial { alpha_mode: AlphaMode::Blend, ..material });
+                        commands.entity(target).insert(handle);
+                        continue;
+                    }
+                    let Some(material) = materials.get_mut(handle) else { continue };
+                    material.base_color.set_a(factor);
+                }
+            },
+        }
+    }
+}
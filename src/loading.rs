@@ -0,0 +1,216 @@
+use bevy::{asset::RecursiveDependencyLoadState, prelude::*};
+
+use crate::state::GameState;
+use crate::asset_loader::LoadingAssets;
+use crate::locale::LocalizedText;
+
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const ERROR_COLOR: Color = Color::rgb(0.9, 0.3, 0.3);
+const BAR_BACKGROUND_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const BAR_FILL_COLOR: Color = Color::rgb(0.35, 0.6, 0.9);
+
+
+#[derive(Component, Debug)]
+pub struct LoadingScreenItem;
+
+
+#[derive(Component, Debug)]
+struct LoadingProgressBar;
+
+
+#[derive(Component, Debug)]
+struct LoadingProgressText;
+
+
+// Set once the asset failures were reported, so the error screen is only built once
+#[derive(Resource, Default, Debug)]
+struct LoadingFailed(bool);
+
+
+pub struct LoadingPlugin;
+
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<LoadingFailed>()
+            .add_systems(OnEnter(GameState::Loading), setup_loading_screen)
+            .add_systems(Update, check_loading_progress.run_if(in_state(GameState::Loading)))
+            .add_systems(OnExit(GameState::Loading), despawn_loading_screen);
+    }
+}
+
+
+fn setup_loading_screen(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+        LoadingScreenItem,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: 60.0,
+                color: TEXT_COLOR,
+                ..default()
+            }).with_style(Style {
+                margin: UiRect::all(Val::Px(20.0)),
+                ..default()
+            }),
+            LocalizedText("loading-title"),
+        ));
+        parent.spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(400.0),
+                height: Val::Px(24.0),
+                ..default()
+            },
+            background_color: BAR_BACKGROUND_COLOR.into(),
+            ..default()
+        }).with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    background_color: BAR_FILL_COLOR.into(),
+                    ..default()
+                },
+                LoadingProgressBar,
+            ));
+        });
+        parent.spawn((
+            TextBundle::from_section("0 / 0", TextStyle {
+                font_size: 24.0,
+                color: TEXT_COLOR,
+                ..default()
+            }).with_style(Style {
+                margin: UiRect::all(Val::Px(10.0)),
+                ..default()
+            }),
+            LoadingProgressText,
+        ));
+    });
+}
+
+
+pub fn check_loading_progress(
+    mut commands: Commands,
+    loading_assets: Res<LoadingAssets>,
+    asset_server: Res<AssetServer>,
+    mut loading_failed: ResMut<LoadingFailed>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut bar_query: Query<&mut Style, With<LoadingProgressBar>>,
+    mut text_query: Query<&mut Text, With<LoadingProgressText>>,
+    screen_query: Query<Entity, With<LoadingScreenItem>>,
+) {
+    if loading_failed.0 {
+        return;
+    }
+
+    let mut loaded = 0;
+    let mut failed = Vec::new();
+    for handle in loading_assets.handles.iter() {
+        // Scenes are only usable once their meshes, materials and textures are in too
+        match asset_server.get_recursive_dependency_load_state(handle.id()) {
+            Some(RecursiveDependencyLoadState::Loaded) => loaded += 1,
+            Some(RecursiveDependencyLoadState::Failed) => {
+                let path = asset_server.get_path(handle.id())
+                    .map_or_else(|| format!("{:?}", handle.id()), |path| path.to_string());
+                failed.push(path);
+            },
+            _ => (),
+        }
+    }
+    failed.extend(loading_assets.errors.iter().cloned());
+
+    let total = loading_assets.handles.len();
+    if let Ok(mut style) = bar_query.get_single_mut() {
+        style.width = Val::Percent(100. * loaded as f32 / total.max(1) as f32);
+    }
+    if let Ok(mut text) = text_query.get_single_mut() {
+        if !text.sections.is_empty() {
+            text.sections[0].value = format!("{} / {}", loaded, total);
+        }
+    }
+
+    if !failed.is_empty() {
+        loading_failed.0 = true;
+        for entity in screen_query.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        spawn_error_screen(&mut commands, &failed);
+        return;
+    }
+    if loaded == total {
+        next_state.set(GameState::Menu);
+    }
+}
+
+
+fn spawn_error_screen(commands: &mut Commands, failed: &[String]) {
+    error!("Failed to load {} asset(s): {}", failed.len(), failed.join(", "));
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+        LoadingScreenItem,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: 48.0,
+                color: ERROR_COLOR,
+                ..default()
+            }).with_style(Style {
+                margin: UiRect::all(Val::Px(20.0)),
+                ..default()
+            }),
+            LocalizedText("loading-failed"),
+        ));
+        for path in failed {
+            parent.spawn(TextBundle::from_section(path.as_str(), TextStyle {
+                font_size: 24.0,
+                color: TEXT_COLOR,
+                ..default()
+            }));
+        }
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: 20.0,
+                color: TEXT_COLOR,
+                ..default()
+            }).with_style(Style {
+                margin: UiRect::all(Val::Px(20.0)),
+                ..default()
+            }),
+            LocalizedText("loading-failed-hint"),
+        ));
+    });
+}
+
+
+fn despawn_loading_screen(mut commands: Commands, query: Query<Entity, With<LoadingScreenItem>>) {
+    for entity in query.iter() {
+        if let Some(entity_commands) = commands.get_entity(entity) {
+            entity_commands.despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource, FluentValue};
+use unic_langid::LanguageIdentifier;
+
+use crate::settings::Settings;
+
+// Generated by build.rs, which also checks that every locale defines the same messages
+include!(concat!(env!("OUT_DIR"), "/locales.rs"));
+
+pub const DEFAULT_LANGUAGE: &'static str = "en-US";
+
+
+/// Messages of the language picked in the settings
+#[derive(Resource)]
+pub struct Locale {
+    language: &'static str,
+    bundle: FluentBundle<FluentResource>,
+    group_separator: String,
+    decimal_separator: String,
+}
+
+
+impl Locale {
+    /// Falls back to the default language when the given one isn't bundled
+    pub fn new(language: &str) -> Self {
+        let (language, source) = LOCALES.iter()
+            .find(|(name, _)| *name == language)
+            .or_else(|| LOCALES.iter().find(|(name, _)| *name == DEFAULT_LANGUAGE))
+            .copied()
+            .expect("the default locale is always bundled");
+        let identifier: LanguageIdentifier = language.parse().expect("locale directories are named by language identifiers");
+        let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, errors)| {
+            error!("Errors in the {} locale: {:?}", language, errors);
+            resource
+        });
+        let mut bundle = FluentBundle::new_concurrent(vec![identifier]);
+        // Bidi isolation marks would show up as boxes in the UI font
+        bundle.set_use_isolating(false);
+        if let Err(errors) = bundle.add_resource(resource) {
+            error!("Errors in the {} locale: {:?}", language, errors);
+        }
+        let mut locale = Self { language, bundle, group_separator: String::new(), decimal_separator: String::new() };
+        locale.group_separator = locale.text("digit-group-separator");
+        locale.decimal_separator = locale.text("decimal-separator");
+        locale
+    }
+
+    pub fn language(&self) -> &'static str {
+        self.language
+    }
+
+    /// Languages of every bundled locale
+    pub fn languages() -> impl Iterator<Item = &'static str> {
+        LOCALES.iter().map(|(name, _)| *name)
+    }
+
+    pub fn text(&self, key: &str) -> String {
+        self.format(key, &[])
+    }
+
+    /// Message with its arguments filled in, or the key itself if the message is missing
+    pub fn format(&self, key: &str, args: &[(&str, FluentValue)]) -> String {
+        let Some(pattern) = self.bundle.get_message(key).and_then(|message| message.value()) else {
+            warn!("Missing message {} in the {} locale", key, self.language);
+            return key.to_string();
+        };
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+        let mut errors = Vec::new();
+        let text = self.bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+        if !errors.is_empty() {
+            warn!("Errors formatting message {}: {:?}", key, errors);
+        }
+        text.into_owned()
+    }
+
+    /// Integer with its digits grouped by thousands, e.g. 12,345 or 12 345
+    pub fn group_digits(&self, value: i64) -> String {
+        let digits = value.unsigned_abs().to_string();
+        let mut grouped = String::new();
+        for (index, digit) in digits.chars().enumerate() {
+            if index > 0 && (digits.len() - index) % 3 == 0 {
+                grouped.push_str(&self.group_separator);
+            }
+            grouped.push(digit);
+        }
+        if value < 0 { format!("-{}", grouped) } else { grouped }
+    }
+
+    /// Number with a fixed count of decimals and the decimal separator of the language
+    pub fn decimal(&self, value: f32, decimals: usize) -> String {
+        let text = format!("{:.*}", decimals, value);
+        let (integer, fraction) = text.split_once('.').unwrap_or((&text, ""));
+        let integer = integer.parse::<i64>().map_or_else(|_| integer.to_string(), |integer| {
+            // Keep the sign of values rounding to -0
+            let sign = if integer == 0 && text.starts_with('-') { "-" } else { "" };
+            format!("{}{}", sign, self.group_digits(integer))
+        });
+        if fraction.is_empty() { integer } else { format!("{}{}{}", integer, self.decimal_separator, fraction) }
+    }
+}
+
+
+impl FromWorld for Locale {
+    fn from_world(world: &mut World) -> Self {
+        let language = world.get_resource::<Settings>().map_or(DEFAULT_LANGUAGE, |settings| settings.language.as_str());
+        Self::new(language)
+    }
+}
+
+
+/// UI text showing a single message, kept up to date when the language changes
+#[derive(Component, Clone, Copy, Debug)]
+pub struct LocalizedText(pub &'static str);
+
+
+pub struct LocalePlugin;
+
+
+impl Plugin for LocalePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Locale>()
+            .add_systems(Update, (
+                apply_language.run_if(resource_changed::<Settings>),
+                update_localized_text,
+            ).chain());
+    }
+}
+
+
+fn apply_language(mut locale: ResMut<Locale>, settings: Res<Settings>) {
+    if locale.language() != settings.language {
+        *locale = Locale::new(&settings.language);
+    }
+}
+
+
+fn update_localized_text(
+    mut query: Query<(Ref<LocalizedText>, &mut Text)>,
+    locale: Res<Locale>,
+) {
+    for (localized, mut text) in query.iter_mut() {
+        if !locale.is_changed() && !localized.is_added() {
+            continue;
+        }
+        if let Some(section) = text.sections.first_mut() {
+            section.value = locale.text(localized.0);
+        }
+    }
+}
@@ -1,58 +1,136 @@
 mod schedule;
+mod interpolation;
 mod state;
+mod settings;
+mod locale;
+mod accessibility;
 mod physics;
 mod asset_loader;
+mod loading;
+mod mesh_colliders;
+#[cfg(feature = "dev-tools")]
 mod debug;
+mod console;
+#[cfg(feature = "dev-tools")]
+mod diagnostics;
 mod field;
 mod camera;
 mod menu;
+mod navigation;
 mod movement;
 mod health;
+mod lifetime;
 mod hud;
+mod scoring;
+mod stats;
+mod particles;
 mod asteroids;
+mod boss;
+mod hazards;
 mod spaceship;
 mod collision_detection;
 mod despawn_routine;
 
 use bevy::prelude::*;
+#[cfg(feature = "inspector")]
+use bevy::input::common_conditions::input_toggle_active;
+#[cfg(feature = "inspector")]
+use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
 use schedule::SchedulePlugin;
+use interpolation::InterpolationPlugin;
 use state::StatePlugin;
+use settings::{Settings, SettingsPlugin};
+use locale::LocalePlugin;
+use accessibility::AccessibilityPlugin;
 use physics::PhysicsPlugin;
 use asset_loader::AssetLoaderPlugin;
+use loading::LoadingPlugin;
+use mesh_colliders::MeshColliderPlugin;
+#[cfg(feature = "dev-tools")]
 use debug::DebugPlugin;
+#[cfg(feature = "dev-tools")]
+use console::ConsolePlugin;
+#[cfg(feature = "dev-tools")]
+use diagnostics::DiagnosticsOverlayPlugin;
 use field::FieldPlugin;
 use camera::CameraPlugin;
 use menu::MenuPlugin;
+use navigation::NavigationPlugin;
 use hud::HudPlugin;
+use scoring::ScoringPlugin;
+use stats::StatsPlugin;
+use lifetime::LifetimePlugin;
+use particles::ParticlePlugin;
+// Lives in the library, so the benchmarks measure the same code
+use tds_proto::pool::{self, PoolPlugin};
 use asteroids::AsteroidPlugin;
+use boss::BossPlugin;
+use hazards::HazardPlugin;
 use spaceship::SpaceshipPlugin;
 use collision_detection::CollisionDetectionPlugin;
 use despawn_routine::DespawnPlugin;
 
 
+#[cfg(feature = "inspector")]
+const INSPECTOR_TOGGLE_KEY: KeyCode = KeyCode::F1;
+
+
 fn main() {
-    App::new()
+    // User preferences have to be known before the window is created
+    let settings = Settings::load();
+
+    let default_plugins = DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(settings.primary_window()),
+        ..default()
+    });
+    #[cfg(feature = "dev-tools")]
+    let default_plugins = default_plugins.set(diagnostics::log_plugin());
+
+    let mut app = App::new();
+    app
         // -- Bevy configuration
         // Built-ins
-        .add_plugins(DefaultPlugins)
+        .add_plugins(default_plugins)
+        .insert_resource(settings)
         // -- Custom user configuration
         // Misc
         .add_plugins(SchedulePlugin)
+        .add_plugins(InterpolationPlugin)
         .add_plugins(StatePlugin)
+        .add_plugins(SettingsPlugin)
+        .add_plugins(LocalePlugin)
+        .add_plugins(AccessibilityPlugin)
         .add_plugins(PhysicsPlugin)
         .add_plugins(AssetLoaderPlugin)
-        .add_plugins(DebugPlugin)
+        .add_plugins(LoadingPlugin)
+        .add_plugins(MeshColliderPlugin)
+        .add_plugins(PoolPlugin)
         // World defaults
         .add_plugins(FieldPlugin)
         .add_plugins(CameraPlugin)
         .add_plugins(MenuPlugin)
+        .add_plugins(NavigationPlugin)
         .add_plugins(HudPlugin)
+        .add_plugins(ParticlePlugin)
         // Game logic
+        .add_plugins(LifetimePlugin)
+        .add_plugins(ScoringPlugin)
+        .add_plugins(StatsPlugin)
         .add_plugins(AsteroidPlugin)
+        .add_plugins(BossPlugin)
+        .add_plugins(HazardPlugin)
         .add_plugins(SpaceshipPlugin)
         .add_plugins(CollisionDetectionPlugin)
-        .add_plugins(DespawnPlugin)
-        // Run the magic
-        .run();
+        .add_plugins(DespawnPlugin);
+    // -- Developer tooling, compiled in through cargo features
+    #[cfg(feature = "dev-tools")]
+    app
+        .add_plugins(DebugPlugin)
+        .add_plugins(ConsolePlugin)
+        .add_plugins(DiagnosticsOverlayPlugin);
+    #[cfg(feature = "inspector")]
+    app.add_plugins(WorldInspectorPlugin::new().run_if(input_toggle_active(false, INSPECTOR_TOGGLE_KEY)));
+    // Run the magic
+    app.run();
 }
@@ -1,38 +1,147 @@
 use bevy::{app::AppExit, prelude::*};
+use fluent_bundle::FluentValue;
 
 use crate::state::GameState;
-use crate::asset_loader::ImageAssets;
+use crate::accessibility::Accessibility;
+use crate::asset_loader::{ImageAssets, SceneAssets};
+use crate::settings::{KeyAction, SettingKind, Settings};
+use crate::stats::{AchievementAssets, Achievements, PlayerStats};
+use crate::asteroids::AsteroidTier;
+use crate::locale::{Locale, LocalizedText};
+use crate::navigation::{Focused, NavigationLock};
 
 const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
 const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.35, 0.35);
 
+const BACK_KEY: KeyCode = KeyCode::Escape;
+const BACK_BUTTON: GamepadButtonType = GamepadButtonType::East;
+
+const SETTINGS_FONT_SIZE: f32 = 24.0;
+const LOCKED_TEXT_COLOR: Color = Color::rgb(0.45, 0.45, 0.45);
+
+const HANGAR_PREVIEW_SCALE: f32 = 3.0;
+const HANGAR_PREVIEW_ROTATION_SPEED: f32 = 0.8;
+
+
+// Which screen of the menu is shown while in `GameState::Menu`
+#[derive(States, Default, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+enum MenuState {
+    Main,
+    Hangar,
+    Stats,
+    Settings,
+    #[default]
+    Disabled,
+}
+
 
 #[derive(Component, Debug)]
 pub struct MainMenuItem;
 
 
+#[derive(Component, Debug)]
+pub struct SettingsMenuItem;
+
+
+#[derive(Component, Debug)]
+pub struct HangarMenuItem;
+
+
+#[derive(Component, Debug)]
+pub struct StatsMenuItem;
+
+
+// Rotating 3D model of the hull currently browsed in the hangar
+#[derive(Component, Debug)]
+struct HangarPreview;
+
+
+#[derive(Component, Debug)]
+struct HangarInfoText;
+
+
+// Index into `SceneAssets::spaceship` of the hull shown in the hangar
+#[derive(Resource, Default, Debug)]
+struct HangarSelection(usize);
+
+
 #[derive(Component, Debug)]
 enum MenuButtonAction {
     Play,
+    Hangar,
+    Stats,
+    Settings,
+    BackToMainMenu,
     Quit,
+    PreviousHull,
+    NextHull,
+    SelectHull,
 }
 
 
+// Button that changes the value of a single setting
+#[derive(Component, Debug)]
+struct SettingsButton(SettingKind);
+
+
+// Text displaying the current value of a single setting
+#[derive(Component, Debug)]
+struct SettingValueText(SettingKind);
+
+
+// Key binding that waits for the next key press
+#[derive(Resource, Default, Debug)]
+struct AwaitingRebind(Option<KeyAction>);
+
+
 pub struct MenuPlugin;
 
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app
-            .add_systems(PostStartup, setup_menu)
-            .add_systems(OnExit(GameState::Menu), despawn_menu)
-            .add_systems(Update, (button_system, menu_action).chain());
+            .init_state::<MenuState>()
+            .init_resource::<AwaitingRebind>()
+            .init_resource::<HangarSelection>()
+            .add_systems(OnEnter(GameState::Menu), open_menu)
+            .add_systems(OnExit(GameState::Menu), close_menu)
+            .add_systems(OnEnter(MenuState::Main), setup_menu)
+            .add_systems(OnExit(MenuState::Main), despawn_menu::<MainMenuItem>)
+            .add_systems(OnEnter(MenuState::Hangar), setup_hangar_menu)
+            .add_systems(OnExit(MenuState::Hangar), (save_settings, despawn_menu::<HangarMenuItem>))
+            .add_systems(OnEnter(MenuState::Stats), setup_stats_menu)
+            .add_systems(OnExit(MenuState::Stats), despawn_menu::<StatsMenuItem>)
+            .add_systems(OnEnter(MenuState::Settings), setup_settings_menu)
+            .add_systems(OnExit(MenuState::Settings), (save_settings, despawn_menu::<SettingsMenuItem>))
+            .add_systems(Update, (button_system, menu_action, menu_back.before(rebind_key)).chain())
+            .add_systems(Update, lock_navigation_while_rebinding.run_if(resource_changed::<AwaitingRebind>))
+            .add_systems(Update, (
+                settings_action,
+                rebind_key,
+                update_setting_values,
+            ).chain().run_if(in_state(MenuState::Settings)))
+            .add_systems(Update, (
+                update_hangar.run_if(resource_changed::<HangarSelection>
+                    .or_else(resource_changed::<Settings>)
+                    .or_else(resource_changed::<Locale>)),
+                rotate_hangar_preview.run_if(|accessibility: Res<Accessibility>| !accessibility.reduced_motion),
+            ).run_if(in_state(MenuState::Hangar)));
     }
 }
 
 
+fn open_menu(mut menu_state: ResMut<NextState<MenuState>>) {
+    menu_state.set(MenuState::Main);
+}
+
+
+fn close_menu(mut menu_state: ResMut<NextState<MenuState>>) {
+    menu_state.set(MenuState::Disabled);
+}
+
+
 fn setup_menu(mut commands: Commands, image_assets: Res<ImageAssets>) {
     // Common style for all buttons on the screen
     let button_style = Style {
@@ -80,19 +189,23 @@ fn setup_menu(mut commands: Commands, image_assets: Res<ImageAssets>) {
             ..default()
         }).with_children(|parent| {
             // Display the game name
-            parent.spawn(TextBundle::from_section(
-            "Main Menu",
-            TextStyle {
-                font_size: 80.0,
-                color: TEXT_COLOR,
-                ..default()
-            }).with_style(Style {
-                margin: UiRect::all(Val::Px(50.0)),
-                ..default()
-            }));
+            parent.spawn((
+                TextBundle::from_section("", TextStyle {
+                    font_size: 80.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                }).with_style(Style {
+                    margin: UiRect::all(Val::Px(50.0)),
+                    ..default()
+                }),
+                LocalizedText("menu-title"),
+            ));
 
-            // Display two buttons for each action available from the main menu:
+            // Display a button for each action available from the main menu:
             // - new game
+            // - hangar
+            // - stats
+            // - settings
             // - quit
             parent.spawn((
                 ButtonBundle {
@@ -108,7 +221,37 @@ fn setup_menu(mut commands: Commands, image_assets: Res<ImageAssets>) {
                     image: UiImage::new(icon),
                     ..default()
                 });
-                parent.spawn(TextBundle::from_section("Play", button_text_style.clone()));
+                parent.spawn((TextBundle::from_section("", button_text_style.clone()), LocalizedText("menu-play")));
+            });
+            parent.spawn((
+                ButtonBundle {
+                    style: button_style.clone(),
+                    background_color: NORMAL_BUTTON.into(),
+                    ..default()
+                },
+                MenuButtonAction::Hangar,
+            )).with_children(|parent| {
+                parent.spawn((TextBundle::from_section("", button_text_style.clone()), LocalizedText("menu-hangar")));
+            });
+            parent.spawn((
+                ButtonBundle {
+                    style: button_style.clone(),
+                    background_color: NORMAL_BUTTON.into(),
+                    ..default()
+                },
+                MenuButtonAction::Stats,
+            )).with_children(|parent| {
+                parent.spawn((TextBundle::from_section("", button_text_style.clone()), LocalizedText("menu-stats")));
+            });
+            parent.spawn((
+                ButtonBundle {
+                    style: button_style.clone(),
+                    background_color: NORMAL_BUTTON.into(),
+                    ..default()
+                },
+                MenuButtonAction::Settings,
+            )).with_children(|parent| {
+                parent.spawn((TextBundle::from_section("", button_text_style.clone()), LocalizedText("menu-settings")));
             });
             parent.spawn((
                 ButtonBundle {
@@ -124,20 +267,460 @@ fn setup_menu(mut commands: Commands, image_assets: Res<ImageAssets>) {
                     image: UiImage::new(icon),
                     ..default()
                 });
-                parent.spawn(TextBundle::from_section("Quit", button_text_style));
+                parent.spawn((TextBundle::from_section("", button_text_style), LocalizedText("menu-quit")));
             });
         });
     });
 }
 
 
-// This system handles changing all buttons color based on mouse interaction
-fn button_system(mut query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>) {
-    for (interaction, mut color) in query.iter_mut() {
-        *color = match *interaction {
-            Interaction::Pressed => PRESSED_BUTTON.into(),
-            Interaction::Hovered => HOVERED_BUTTON.into(),
-            _ => NORMAL_BUTTON.into(),
+fn setup_hangar_menu(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+    settings: Res<Settings>,
+    mut selection: ResMut<HangarSelection>,
+) {
+    // Start browsing from the hull that is currently equipped
+    selection.0 = settings.selected_hull.as_deref()
+        .and_then(|name| scene_assets.spaceship.iter().position(|model| model.name == name))
+        .unwrap_or(0);
+
+    let button_style = Style {
+        width: Val::Px(180.0),
+        height: Val::Px(65.0),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font_size: 32.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    // The middle of the screen is left empty for the 3D preview
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::SpaceBetween,
+                ..default()
+            },
+            ..default()
+        },
+        HangarMenuItem,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: 60.0,
+                color: TEXT_COLOR,
+                ..default()
+            }).with_style(Style {
+                margin: UiRect::all(Val::Px(20.0)),
+                ..default()
+            }),
+            LocalizedText("menu-hangar"),
+        ));
+
+        parent.spawn(NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(20.0)),
+                ..default()
+            },
+            ..default()
+        }).with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", TextStyle {
+                    font_size: SETTINGS_FONT_SIZE,
+                    color: TEXT_COLOR,
+                    ..default()
+                }).with_text_justify(JustifyText::Center),
+                HangarInfoText,
+            ));
+            parent.spawn(NodeBundle::default()).with_children(|parent| {
+                // Arrows need no translation, the other labels are message keys
+                for (action, label, localized) in [
+                    (MenuButtonAction::PreviousHull, "<", false),
+                    (MenuButtonAction::SelectHull, "hangar-select", true),
+                    (MenuButtonAction::NextHull, ">", false),
+                    (MenuButtonAction::BackToMainMenu, "menu-back", true),
+                ] {
+                    parent.spawn((
+                        ButtonBundle {
+                            style: button_style.clone(),
+                            background_color: NORMAL_BUTTON.into(),
+                            ..default()
+                        },
+                        action,
+                    )).with_children(|parent| {
+                        let mut text = parent.spawn(TextBundle::from_section(label, button_text_style.clone()));
+                        if localized {
+                            text.insert(LocalizedText(label));
+                        }
+                    });
+                }
+            });
+        });
+    });
+}
+
+
+// Rebuilds the preview model and the stats description of the browsed hull
+fn update_hangar(
+    mut commands: Commands,
+    preview_query: Query<Entity, With<HangarPreview>>,
+    mut text_query: Query<&mut Text, With<HangarInfoText>>,
+    scene_assets: Res<SceneAssets>,
+    settings: Res<Settings>,
+    selection: Res<HangarSelection>,
+    locale: Res<Locale>,
+) {
+    let Some(model) = scene_assets.spaceship.get(selection.0) else { return };
+    let hull = model.hull.clone().unwrap_or_default();
+
+    for entity in preview_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_scale(Vec3::splat(model.scale * HANGAR_PREVIEW_SCALE))),
+        HangarPreview,
+        HangarMenuItem,
+    )).with_children(|parent| {
+        parent.spawn(model.scene_bundle());
+    });
+
+    let Ok(mut text) = text_query.get_single_mut() else { return };
+    if text.sections.is_empty() {
+        return;
+    }
+    let name = if hull.display_name.is_empty() { model.name.as_str() } else { hull.display_name.as_str() };
+    let equipped = settings.selected_hull.as_deref() == Some(model.name.as_str())
+        || (settings.selected_hull.is_none() && selection.0 == 0);
+    let weapon = &hull.weapon;
+    text.sections[0].value = [
+        locale.format("hangar-hull", &[
+            ("name", name.into()),
+            ("equipped", if equipped { "yes" } else { "no" }.into()),
+        ]),
+        locale.format("hangar-stats", &[
+            ("speed", locale.decimal(hull.speed, 0).into()),
+            ("turn_rate", locale.decimal(hull.turn_rate, 1).into()),
+            ("radius", locale.decimal(model.collider_shape.radius() * model.scale, 1).into()),
+            ("health", locale.decimal(hull.health, 0).into()),
+        ]),
+        locale.format("hangar-weapon", &[
+            ("count", weapon.count.into()),
+            ("damage", locale.decimal(weapon.damage, 0).into()),
+            ("cooldown", locale.decimal(weapon.cooldown, 2).into()),
+            ("flak", weapon.flak.into()),
+        ]),
+        locale.format("hangar-tags", &[("tags", model.tags.join(", ").into())]),
+    ].join("\n");
+}
+
+
+fn rotate_hangar_preview(mut query: Query<&mut Transform, With<HangarPreview>>, time: Res<Time>) {
+    for mut transform in query.iter_mut() {
+        transform.rotate_y(HANGAR_PREVIEW_ROTATION_SPEED * time.delta_seconds());
+    }
+}
+
+
+fn setup_stats_menu(
+    mut commands: Commands,
+    stats: Res<PlayerStats>,
+    achievement_assets: Res<AchievementAssets>,
+    achievements: Res<Assets<Achievements>>,
+    locale: Res<Locale>,
+) {
+    let text_style = TextStyle {
+        font_size: SETTINGS_FONT_SIZE,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    let title_style = TextStyle {
+        font_size: 36.0,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    let column_style = Style {
+        flex_direction: FlexDirection::Column,
+        margin: UiRect::all(Val::Px(20.0)),
+        ..default()
+    };
+
+    let count = |value: u32| -> FluentValue { locale.group_digits(value as i64).into() };
+    let mut lines = vec![
+        locale.format("stats-runs-played", &[("count", count(stats.runs_played))]),
+        locale.format("stats-best-score", &[("score", locale.group_digits(stats.best_score as i64).into())]),
+        locale.format("stats-longest-survival", &[("duration", format_duration(stats.longest_survival).into())]),
+        locale.format("stats-total-play-time", &[("duration", format_duration(stats.total_play_time).into())]),
+        locale.format("stats-shots-fired", &[("count", count(stats.shots_fired))]),
+        locale.format("stats-hits", &[
+            ("count", count(stats.hits)),
+            ("accuracy", locale.decimal(stats.accuracy(), 1).into()),
+        ]),
+        locale.format("stats-damage-taken", &[("amount", locale.decimal(stats.damage_taken, 0).into())]),
+        locale.format("stats-asteroids-destroyed", &[("count", count(stats.total_asteroids_destroyed()))]),
+    ];
+    for tier in AsteroidTier::ALL {
+        let destroyed = stats.asteroids_destroyed.get(&tier).copied().unwrap_or(0);
+        let line = locale.format("stats-asteroids-of-tier", &[
+            ("tier", locale.text(tier.label_key()).into()),
+            ("count", count(destroyed)),
+        ]);
+        lines.push(format!("    {}", line));
+    }
+
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+        StatsMenuItem,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: 60.0,
+                color: TEXT_COLOR,
+                ..default()
+            }).with_style(Style {
+                margin: UiRect::all(Val::Px(20.0)),
+                ..default()
+            }),
+            LocalizedText("menu-stats"),
+        ));
+
+        parent.spawn(NodeBundle::default()).with_children(|parent| {
+            parent.spawn(NodeBundle {
+                style: column_style.clone(),
+                ..default()
+            }).with_children(|parent| {
+                parent.spawn((TextBundle::from_section("", title_style.clone()), LocalizedText("stats-lifetime")));
+                for line in lines {
+                    parent.spawn(TextBundle::from_section(line, text_style.clone()));
+                }
+            });
+
+            // Locked achievements are greyed out
+            parent.spawn(NodeBundle {
+                style: column_style,
+                ..default()
+            }).with_children(|parent| {
+                parent.spawn((TextBundle::from_section("", title_style), LocalizedText("stats-achievements")));
+                let Some(achievements) = achievements.get(&achievement_assets.achievements) else { return };
+                for achievement in achievements.achievements.iter() {
+                    let color = if stats.is_unlocked(&achievement.id) { TEXT_COLOR } else { LOCKED_TEXT_COLOR };
+                    parent.spawn(TextBundle::from_section(
+                        format!("{} - {}", locale.text(&achievement.name_key), locale.text(&achievement.description_key)),
+                        TextStyle { color, ..text_style.clone() },
+                    ));
+                }
+            });
+        });
+
+        parent.spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(65.0),
+                    margin: UiRect::all(Val::Px(20.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            MenuButtonAction::BackToMainMenu,
+        )).with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", TextStyle {
+                    font_size: 40.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                }),
+                LocalizedText("menu-back"),
+            ));
+        });
+    });
+}
+
+
+fn format_duration(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+
+fn setup_settings_menu(mut commands: Commands, settings: Res<Settings>, locale: Res<Locale>) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+        SettingsMenuItem,
+    )).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: 60.0,
+                color: TEXT_COLOR,
+                ..default()
+            }).with_style(Style {
+                margin: UiRect::all(Val::Px(20.0)),
+                ..default()
+            }),
+            LocalizedText("menu-settings"),
+        ));
+
+        // General settings go next to key bindings, with the accessibility options below those
+        parent.spawn(NodeBundle::default()).with_children(|parent| {
+            spawn_settings_column(parent, "settings-general", &SettingKind::GENERAL, &settings, &locale);
+            parent.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ..default()
+            }).with_children(|parent| {
+                let controls: Vec<SettingKind> = KeyAction::ALL.into_iter().map(SettingKind::KeyBinding).collect();
+                spawn_settings_column(parent, "settings-controls", &controls, &settings, &locale);
+                spawn_settings_column(parent, "settings-accessibility", &SettingKind::ACCESSIBILITY, &settings, &locale);
+            });
+        });
+
+        parent.spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(250.0),
+                    height: Val::Px(65.0),
+                    margin: UiRect::all(Val::Px(20.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            MenuButtonAction::BackToMainMenu,
+        )).with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section("", TextStyle {
+                    font_size: 40.0,
+                    color: TEXT_COLOR,
+                    ..default()
+                }),
+                LocalizedText("menu-back"),
+            ));
+        });
+    });
+}
+
+
+// Spawns a titled column with a "label [value]" row for every given setting
+fn spawn_settings_column(
+    parent: &mut ChildBuilder,
+    title_key: &'static str,
+    kinds: &[SettingKind],
+    settings: &Settings,
+    locale: &Locale,
+) {
+    let row_style = Style {
+        width: Val::Px(460.0),
+        margin: UiRect::all(Val::Px(4.0)),
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::SpaceBetween,
+        ..default()
+    };
+    let value_button_style = Style {
+        width: Val::Px(200.0),
+        height: Val::Px(40.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = TextStyle {
+        font_size: SETTINGS_FONT_SIZE,
+        color: TEXT_COLOR,
+        ..default()
+    };
+
+    parent.spawn(NodeBundle {
+        style: Style {
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            margin: UiRect::all(Val::Px(10.0)),
+            ..default()
+        },
+        ..default()
+    }).with_children(|parent| {
+        parent.spawn((
+            TextBundle::from_section("", TextStyle {
+                font_size: 36.0,
+                color: TEXT_COLOR,
+                ..default()
+            }).with_style(Style {
+                margin: UiRect::all(Val::Px(10.0)),
+                ..default()
+            }),
+            LocalizedText(title_key),
+        ));
+        for &kind in kinds {
+            parent.spawn(NodeBundle {
+                style: row_style.clone(),
+                ..default()
+            }).with_children(|parent| {
+                parent.spawn((TextBundle::from_section("", text_style.clone()), LocalizedText(kind.label_key())));
+                parent.spawn((
+                    ButtonBundle {
+                        style: value_button_style.clone(),
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    SettingsButton(kind),
+                )).with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(settings.describe(kind, locale), text_style.clone()),
+                        SettingValueText(kind),
+                    ));
+                });
+            });
+        }
+    });
+}
+
+
+// This system handles changing all buttons color based on mouse interaction and keyboard/gamepad focus
+fn button_system(mut query: Query<(&Interaction, Has<Focused>, &mut BackgroundColor), With<Button>>) {
+    for (interaction, focused, mut color) in query.iter_mut() {
+        let target = match *interaction {
+            Interaction::Pressed => PRESSED_BUTTON,
+            Interaction::Hovered => HOVERED_BUTTON,
+            Interaction::None if focused => HOVERED_BUTTON,
+            Interaction::None => NORMAL_BUTTON,
+        };
+        if color.0 != target {
+            color.0 = target;
         }
     }
 }
@@ -147,19 +730,126 @@ fn menu_action(
     query: Query<(&Interaction, &MenuButtonAction), (Changed<Interaction>, With<Button>)>,
     mut app_exit_events: EventWriter<AppExit>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut selection: ResMut<HangarSelection>,
+    mut settings: ResMut<Settings>,
+    scene_assets: Res<SceneAssets>,
 ) {
+    let hull_count = scene_assets.spaceship.len().max(1);
     for (interaction, menu_button_action) in query.iter() {
         if *interaction == Interaction::Pressed {
             match menu_button_action {
                 MenuButtonAction::Quit => { app_exit_events.send(AppExit); },
                 MenuButtonAction::Play => { next_state.set(GameState::InGame); },
+                MenuButtonAction::Hangar => { menu_state.set(MenuState::Hangar); },
+                MenuButtonAction::Stats => { menu_state.set(MenuState::Stats); },
+                MenuButtonAction::Settings => { menu_state.set(MenuState::Settings); },
+                MenuButtonAction::BackToMainMenu => { menu_state.set(MenuState::Main); },
+                MenuButtonAction::PreviousHull => { selection.0 = (selection.0 + hull_count - 1) % hull_count; },
+                MenuButtonAction::NextHull => { selection.0 = (selection.0 + 1) % hull_count; },
+                MenuButtonAction::SelectHull => {
+                    let Some(model) = scene_assets.spaceship.get(selection.0) else { continue };
+                    settings.selected_hull = Some(model.name.clone());
+                },
             }
         }
     }
 }
 
 
-fn despawn_menu(mut commands: Commands, query: Query<Entity, With<MainMenuItem>>) {
+// Escape or B leaves the current screen for the main menu
+fn menu_back(
+    menu_state: Res<State<MenuState>>,
+    mut next_menu_state: ResMut<NextState<MenuState>>,
+    awaiting_rebind: Res<AwaitingRebind>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+) {
+    if matches!(menu_state.get(), MenuState::Main | MenuState::Disabled) {
+        return;
+    }
+    // Escape cancels a rebinding instead
+    if awaiting_rebind.0.is_some() || awaiting_rebind.is_changed() {
+        return;
+    }
+    let back = keyboard_input.just_pressed(BACK_KEY)
+        || gamepads.iter().any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, BACK_BUTTON)));
+    if back {
+        next_menu_state.set(MenuState::Main);
+    }
+}
+
+
+fn lock_navigation_while_rebinding(awaiting_rebind: Res<AwaitingRebind>, mut navigation_lock: ResMut<NavigationLock>) {
+    navigation_lock.0 = awaiting_rebind.0.is_some();
+}
+
+
+fn settings_action(
+    query: Query<(&Interaction, &SettingsButton), Changed<Interaction>>,
+    mut settings: ResMut<Settings>,
+    mut awaiting_rebind: ResMut<AwaitingRebind>,
+) {
+    for (interaction, &SettingsButton(kind)) in query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match kind {
+            SettingKind::KeyBinding(action) => awaiting_rebind.0 = Some(action),
+            _ => settings.cycle(kind),
+        }
+    }
+}
+
+
+fn rebind_key(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut settings: ResMut<Settings>,
+    mut awaiting_rebind: ResMut<AwaitingRebind>,
+) {
+    let Some(action) = awaiting_rebind.0 else { return };
+    // The key that started the rebinding, e.g. Enter, isn't the new binding
+    if awaiting_rebind.is_changed() {
+        return;
+    }
+    let Some(&key) = keyboard_input.get_just_pressed().next() else { return };
+    // Escape cancels the rebinding
+    if key != KeyCode::Escape {
+        settings.key_bindings.set(action, key);
+    }
+    awaiting_rebind.0 = None;
+}
+
+
+fn update_setting_values(
+    mut query: Query<(&mut Text, &SettingValueText)>,
+    settings: Res<Settings>,
+    awaiting_rebind: Res<AwaitingRebind>,
+    locale: Res<Locale>,
+) {
+    if !settings.is_changed() && !awaiting_rebind.is_changed() && !locale.is_changed() {
+        return;
+    }
+    for (mut text, &SettingValueText(kind)) in query.iter_mut() {
+        if text.sections.is_empty() {
+            continue;
+        }
+        text.sections[0].value = match kind {
+            SettingKind::KeyBinding(action) if awaiting_rebind.0 == Some(action) => locale.text("settings-press-key"),
+            _ => settings.describe(kind, &locale),
+        };
+    }
+}
+
+
+fn save_settings(settings: Res<Settings>, mut awaiting_rebind: ResMut<AwaitingRebind>) {
+    awaiting_rebind.0 = None;
+    settings.save();
+}
+
+
+fn despawn_menu<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
     for entity in query.iter() {
         if let Some(entity_commands) = commands.get_entity(entity) {
             entity_commands.despawn_recursive();
@@ -0,0 +1,115 @@
+use bevy::{
+    prelude::*,
+    render::mesh::VertexAttributeValues,
+    utils::HashMap,
+};
+use bevy_rapier3d::prelude::*;
+
+use crate::state::GameState;
+use crate::asset_loader::{ColliderShape, SceneAssets};
+
+
+/// Colliders computed from model meshes, so every model and debris mesh is only processed once
+#[derive(Resource, Default, Debug)]
+pub struct MeshColliderCache {
+    scenes: HashMap<AssetId<Scene>, Option<Collider>>,
+    meshes: HashMap<AssetId<Mesh>, Option<Collider>>,
+}
+
+
+impl MeshColliderCache {
+    /// Convex hull of a single mesh in its local space
+    pub fn mesh_collider(&mut self, mesh: &Handle<Mesh>, meshes: &Assets<Mesh>) -> Option<Collider> {
+        self.meshes.entry(mesh.id())
+            .or_insert_with(|| {
+                let mesh = meshes.get(mesh)?;
+                Collider::from_bevy_mesh(mesh, &ComputedColliderShape::ConvexHull)
+            })
+            .clone()
+    }
+}
+
+
+// Vertices and triangles of all meshes of a scene, in the scene's root space
+#[derive(Default, Debug)]
+struct SceneGeometry {
+    vertices: Vec<Vec3>,
+    indices: Vec<[u32; 3]>,
+}
+
+
+pub struct MeshColliderPlugin;
+
+
+impl Plugin for MeshColliderPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<MeshColliderCache>()
+            // Every scene and its meshes are loaded by the time the loading screen is left
+            .add_systems(OnExit(GameState::Loading), build_model_colliders);
+    }
+}
+
+
+fn build_model_colliders(
+    mut scene_assets: ResMut<SceneAssets>,
+    mut cache: ResMut<MeshColliderCache>,
+    scenes: Res<Assets<Scene>>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    for model in scene_assets.iter_mut() {
+        if !model.collider_shape.is_mesh_based() {
+            continue;
+        }
+        let collider = cache.scenes.entry(model.scene.id())
+            .or_insert_with(|| {
+                let geometry = scenes.get(&model.scene)
+                    .map(|scene| scene_geometry(scene, &meshes))
+                    .unwrap_or_default();
+                match model.collider_shape {
+                    ColliderShape::ConvexDecomposition(_) if !geometry.indices.is_empty() => {
+                        Some(Collider::convex_decomposition(&geometry.vertices, &geometry.indices))
+                    },
+                    _ => Collider::convex_hull(&geometry.vertices),
+                }
+            })
+            .clone()
+            // The scene is spawned as a child rotated by the forward correction, so the collider is too
+            .map(|collider| if model.forward_correction == Quat::IDENTITY {
+                collider
+            } else {
+                Collider::compound(vec![(Vec3::ZERO, model.forward_correction, collider)])
+            });
+        if collider.is_none() {
+            warn!("Model '{}' has no usable mesh geometry, falling back to a ball collider", model.name);
+        }
+        model.mesh_collider = collider;
+    }
+}
+
+
+fn scene_geometry(scene: &Scene, meshes: &Assets<Mesh>) -> SceneGeometry {
+    let mut geometry = SceneGeometry::default();
+    for entity in scene.world.iter_entities() {
+        let Some(mesh) = entity.get::<Handle<Mesh>>().and_then(|handle| meshes.get(handle)) else { continue };
+        let Some(VertexAttributeValues::Float32x3(positions)) = mesh.attribute(Mesh::ATTRIBUTE_POSITION) else { continue };
+
+        // Scene worlds are never propagated, so walk up the hierarchy by hand
+        let mut transform = entity.get::<Transform>().copied().unwrap_or_default().compute_affine();
+        let mut parent = entity.get::<Parent>().map(|parent| parent.get());
+        while let Some(current) = parent {
+            let Some(parent_ref) = scene.world.get_entity(current) else { break };
+            let parent_transform = parent_ref.get::<Transform>().copied().unwrap_or_default();
+            transform = parent_transform.compute_affine() * transform;
+            parent = parent_ref.get::<Parent>().map(|parent| parent.get());
+        }
+
+        let base = geometry.vertices.len() as u32;
+        geometry.vertices.extend(positions.iter().map(|&position| transform.transform_point3(position.into())));
+        if let Some(indices) = mesh.indices() {
+            let indices: Vec<u32> = indices.iter().map(|index| base + index as u32).collect();
+            geometry.indices.extend(indices.chunks_exact(3).map(|triangle| [triangle[0], triangle[1], triangle[2]]));
+        }
+    }
+    geometry
+}
@@ -4,6 +4,8 @@ use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use rand::Rng;
 
+use crate::interpolation::InterpolatedTransform;
+
 
 #[derive(Default, Debug)]
 pub struct DirVector {
@@ -48,6 +50,10 @@ pub struct MovingObjectBundle {
     pub sleeping: Sleeping,
     pub ccd: Ccd,
     pub active_events: ActiveEvents,
+    /// Set every tick from the gravity wells in range
+    pub external_force: ExternalForce,
+    pub mass_properties: ReadMassProperties,
+    pub interpolation: InterpolatedTransform,
 }
 
 
@@ -60,6 +66,9 @@ impl Default for MovingObjectBundle {
             sleeping: Sleeping::disabled(),
             ccd: Ccd::enabled(),
             active_events: ActiveEvents::COLLISION_EVENTS,
+            external_force: ExternalForce::default(),
+            mass_properties: ReadMassProperties::default(),
+            interpolation: InterpolatedTransform::default(),
             // This is the part you usually want to change
             velocity: Velocity::default(),
             collider: Collider::default(),
@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+
+const STICK_THRESHOLD: f32 = 0.5;
+// Delay before a held stick moves the focus again
+const STICK_REPEAT_SECONDS: f32 = 0.25;
+// How much sideways offset counts against a candidate, compared to distance along the direction
+const PERPENDICULAR_WEIGHT: f32 = 2.;
+
+const ACTIVATE_KEYS: [KeyCode; 2] = [KeyCode::Enter, KeyCode::NumpadEnter];
+const ACTIVATE_BUTTON: GamepadButtonType = GamepadButtonType::South;
+
+
+/// Button that keyboard and gamepad input acts on, only one at a time
+#[derive(Component, Debug)]
+pub struct Focused;
+
+
+/// Suspends navigation, e.g. while a key binding waits for its key
+#[derive(Resource, Default, Debug)]
+pub struct NavigationLock(pub bool);
+
+
+#[derive(Resource, Debug)]
+struct StickRepeat {
+    timer: Timer,
+    held: bool,
+}
+
+
+impl Default for StickRepeat {
+    fn default() -> Self {
+        Self { timer: Timer::from_seconds(STICK_REPEAT_SECONDS, TimerMode::Once), held: false }
+    }
+}
+
+
+// Button pressed through navigation, released again on the next frame
+#[derive(Resource, Default, Debug)]
+struct NavigationPress(Option<Entity>);
+
+
+pub struct NavigationPlugin;
+
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<NavigationLock>()
+            .init_resource::<StickRepeat>()
+            .init_resource::<NavigationPress>()
+            .add_systems(PreUpdate, (
+                release_navigation_press,
+                focus_hovered_button,
+                (move_focus, activate_focused_button).run_if(|lock: Res<NavigationLock>| !lock.0),
+            ).chain().after(bevy::ui::UiSystem::Focus));
+    }
+}
+
+
+fn release_navigation_press(mut press: ResMut<NavigationPress>, mut query: Query<&mut Interaction>) {
+    let Some(entity) = press.0.take() else { return };
+    let Ok(mut interaction) = query.get_mut(entity) else { return };
+    if *interaction == Interaction::Pressed {
+        *interaction = Interaction::None;
+    }
+}
+
+
+// The mouse and the keyboard share the same highlight
+fn focus_hovered_button(
+    mut commands: Commands,
+    query: Query<(Entity, &Interaction), (Changed<Interaction>, With<Button>)>,
+    focused_query: Query<Entity, With<Focused>>,
+) {
+    for (entity, interaction) in query.iter() {
+        if *interaction != Interaction::Hovered {
+            continue;
+        }
+        for focused in focused_query.iter().filter(|&focused| focused != entity) {
+            commands.entity(focused).remove::<Focused>();
+        }
+        commands.entity(entity).insert(Focused);
+    }
+}
+
+
+fn navigation_direction(
+    keyboard_input: &ButtonInput<KeyCode>,
+    gamepads: &Gamepads,
+    gamepad_buttons: &ButtonInput<GamepadButton>,
+    gamepad_axes: &Axis<GamepadAxis>,
+    stick_repeat: &mut StickRepeat,
+    time: &Time,
+) -> Option<Vec2> {
+    // UI coordinates grow downwards
+    for (key, direction) in [
+        (KeyCode::ArrowUp, Vec2::NEG_Y),
+        (KeyCode::ArrowDown, Vec2::Y),
+        (KeyCode::ArrowLeft, Vec2::NEG_X),
+        (KeyCode::ArrowRight, Vec2::X),
+    ] {
+        if keyboard_input.just_pressed(key) {
+            return Some(direction);
+        }
+    }
+
+    let mut stick = Vec2::ZERO;
+    for gamepad in gamepads.iter() {
+        for (button, direction) in [
+            (GamepadButtonType::DPadUp, Vec2::NEG_Y),
+            (GamepadButtonType::DPadDown, Vec2::Y),
+            (GamepadButtonType::DPadLeft, Vec2::NEG_X),
+            (GamepadButtonType::DPadRight, Vec2::X),
+        ] {
+            if gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button)) {
+                return Some(direction);
+            }
+        }
+        let x = gamepad_axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX)).unwrap_or(0.);
+        let y = gamepad_axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY)).unwrap_or(0.);
+        if Vec2::new(x, y).length() > stick.length() {
+            stick = Vec2::new(x, -y);
+        }
+    }
+
+    // A held stick repeats after a delay, letting go of it resets the delay
+    if stick.length() < STICK_THRESHOLD {
+        stick_repeat.held = false;
+        return None;
+    }
+    stick_repeat.timer.tick(time.delta());
+    if stick_repeat.held && !stick_repeat.timer.finished() {
+        return None;
+    }
+    stick_repeat.held = true;
+    stick_repeat.timer.reset();
+    let direction = if stick.x.abs() > stick.y.abs() { Vec2::new(stick.x.signum(), 0.) } else { Vec2::new(0., stick.y.signum()) };
+    Some(direction)
+}
+
+
+// Moves the focus to the closest button in the pressed direction, or to the first button if none is focused
+fn move_focus(
+    mut commands: Commands,
+    button_query: Query<(Entity, &Node, &GlobalTransform, &ViewVisibility, Has<Focused>), With<Button>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    mut stick_repeat: ResMut<StickRepeat>,
+    time: Res<Time>,
+) {
+    let Some(direction) = navigation_direction(
+        &keyboard_input, &gamepads, &gamepad_buttons, &gamepad_axes, &mut stick_repeat, &time,
+    ) else { return };
+
+    let buttons: Vec<(Entity, Vec2, bool)> = button_query.iter()
+        .filter(|(_, node, _, visibility, _)| visibility.get() && node.size() != Vec2::ZERO)
+        .map(|(entity, _, transform, _, focused)| (entity, transform.translation().truncate(), focused))
+        .collect();
+    let current = buttons.iter().find(|(_, _, focused)| *focused).copied();
+    let target = match current {
+        Some((_, from, _)) => buttons.iter()
+            .filter_map(|&(entity, position, _)| {
+                let offset = position - from;
+                let along = offset.dot(direction);
+                (along > 0.).then(|| (entity, along + PERPENDICULAR_WEIGHT * offset.perp_dot(direction).abs()))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(entity, _)| entity),
+        // Reading order: top to bottom, then left to right
+        None => buttons.iter()
+            .min_by(|(_, a, _), (_, b, _)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)))
+            .map(|&(entity, _, _)| entity),
+    };
+    let Some(target) = target else { return };
+    if let Some((focused, _, _)) = current {
+        commands.entity(focused).remove::<Focused>();
+    }
+    commands.entity(target).insert(Focused);
+}
+
+
+fn activate_focused_button(
+    mut query: Query<(Entity, &mut Interaction), (With<Focused>, With<Button>)>,
+    mut press: ResMut<NavigationPress>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+) {
+    let activated = keyboard_input.any_just_pressed(ACTIVATE_KEYS)
+        || gamepads.iter().any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(gamepad, ACTIVATE_BUTTON)));
+    if !activated {
+        return;
+    }
+    let Ok((entity, mut interaction)) = query.get_single_mut() else { return };
+    *interaction = Interaction::Pressed;
+    press.0 = Some(entity);
+}
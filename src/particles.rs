@@ -0,0 +1,339 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{de::Error, Deserialize, Deserializer};
+
+use crate::state::GameState;
+use crate::asset_loader::{LoadingAssets, RonAssetLoader};
+use crate::despawn_routine::DisposableEntity;
+use crate::lifetime::Lifetime;
+use crate::pool::{EntityPool, Idle, PoolKind};
+use crate::accessibility::Accessibility;
+
+const PARTICLE_EFFECTS_PATH: &'static str = "effects.particles.ron";
+const PARTICLE_RADIUS: f32 = 0.5;
+const PARTICLE_DRAG: f32 = 1.5;
+
+
+#[derive(Deserialize, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ParticleEffectKind {
+    AsteroidExplosion,
+    MissileImpact,
+    EngineExhaust,
+    ShipDeath,
+}
+
+
+impl ParticleEffectKind {
+    /// Bright bursts, left out with reduced motion
+    fn is_flash(self) -> bool {
+        matches!(self, Self::AsteroidExplosion | Self::MissileImpact | Self::ShipDeath)
+    }
+}
+
+
+/// Emitter definition as described in the effects data file
+#[derive(Deserialize, Clone, Debug)]
+pub struct ParticleEffect {
+    /// Particles spawned at once by a burst
+    #[serde(default)]
+    pub burst: u32,
+    /// Particles spawned per second by a continuous emitter
+    #[serde(default)]
+    pub rate: f32,
+    #[serde(deserialize_with = "deserialize_range")]
+    pub lifetime: (f32, f32),
+    #[serde(deserialize_with = "deserialize_range")]
+    pub speed: (f32, f32),
+    /// Half-angle of the emission cone in degrees; 180 emits in every direction
+    #[serde(deserialize_with = "deserialize_spread")]
+    pub spread: f32,
+    /// Keyframes over the normalized particle age
+    pub color: Vec<(f32, [f32; 4])>,
+    pub size: Vec<(f32, f32)>,
+}
+
+
+impl ParticleEffect {
+    fn color_at(&self, t: f32) -> Color {
+        let [r, g, b, a] = sample_curve(&self.color, t, |a, b, k| {
+            std::array::from_fn(|i| a[i] + (b[i] - a[i]) * k)
+        }).unwrap_or([1.; 4]);
+        Color::rgba(r, g, b, a)
+    }
+
+    fn size_at(&self, t: f32) -> f32 {
+        sample_curve(&self.size, t, |a, b, k| a + (b - a) * k).unwrap_or(1.)
+    }
+}
+
+
+// Ranges are sampled with `gen_range`, which panics on reversed or non-finite bounds
+fn deserialize_range<'de, D: Deserializer<'de>>(deserializer: D) -> Result<(f32, f32), D::Error> {
+    let (a, b) = <(f32, f32)>::deserialize(deserializer)?;
+    if !a.is_finite() || !b.is_finite() {
+        return Err(D::Error::custom(format!("range ({}, {}) is not made of numbers", a, b)));
+    }
+    Ok((a.min(b), a.max(b)))
+}
+
+
+fn deserialize_spread<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+    let spread = f32::deserialize(deserializer)?;
+    if !spread.is_finite() {
+        return Err(D::Error::custom(format!("spread {} is not a number", spread)));
+    }
+    Ok(spread.abs())
+}
+
+
+// Linear interpolation between sorted keyframes
+fn sample_curve<T: Copy>(keys: &[(f32, T)], t: f32, lerp: impl Fn(T, T, f32) -> T) -> Option<T> {
+    let first = keys.first()?;
+    if t <= first.0 {
+        return Some(first.1);
+    }
+    for window in keys.windows(2) {
+        let ((t0, v0), (t1, v1)) = (window[0], window[1]);
+        if t <= t1 {
+            let k = if t1 > t0 { (t - t0) / (t1 - t0) } else { 1. };
+            return Some(lerp(v0, v1, k));
+        }
+    }
+    keys.last().map(|key| key.1)
+}
+
+
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct ParticleEffects {
+    pub effects: HashMap<ParticleEffectKind, ParticleEffect>,
+}
+
+
+#[derive(Resource, Default, Debug)]
+pub struct ParticleAssets {
+    pub effects: Handle<ParticleEffects>,
+    pub mesh: Handle<Mesh>,
+}
+
+
+/// One-shot burst of particles at a point
+#[derive(Event, Debug)]
+pub struct ParticleBurstEvent {
+    pub kind: ParticleEffectKind,
+    pub translation: Vec3,
+    /// Center of the emission cone, zero to emit in every direction
+    pub direction: Vec3,
+}
+
+
+/// Continuous emitter attached to an entity, emits along its local `direction`
+#[derive(Component, Debug)]
+pub struct ParticleEmitter {
+    pub kind: ParticleEffectKind,
+    pub active: bool,
+    pub offset: Vec3,
+    pub direction: Vec3,
+    accumulator: f32,
+}
+
+
+impl ParticleEmitter {
+    pub fn new(kind: ParticleEffectKind, offset: Vec3, direction: Vec3) -> Self {
+        Self { kind, active: false, offset, direction, accumulator: 0. }
+    }
+}
+
+
+#[derive(Component, Debug)]
+pub struct Particle {
+    kind: ParticleEffectKind,
+    velocity: Vec3,
+}
+
+
+pub struct ParticlePlugin;
+
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_asset::<ParticleEffects>()
+            .register_asset_loader(RonAssetLoader::<ParticleEffects>::new(&["particles.ron"]))
+            .init_resource::<ParticleAssets>()
+            .add_event::<ParticleBurstEvent>()
+            .add_systems(Startup, load_particle_assets)
+            // Purely visual, so they move every frame rather than every tick
+            .add_systems(Update, (
+                emit_particle_bursts,
+                emit_continuous_particles,
+                update_particles,
+            ).chain().run_if(in_state(GameState::InGame)));
+    }
+}
+
+
+fn load_particle_assets(
+    mut particle_assets: ResMut<ParticleAssets>,
+    mut loading_assets: ResMut<LoadingAssets>,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    *particle_assets = ParticleAssets {
+        effects: asset_server.load(PARTICLE_EFFECTS_PATH),
+        mesh: meshes.add(Sphere::new(PARTICLE_RADIUS).mesh().uv(8, 6)),
+    };
+    loading_assets.track(&particle_assets.effects);
+}
+
+
+fn emit_particle_bursts(
+    mut commands: Commands,
+    mut particle_burst_event_reader: EventReader<ParticleBurstEvent>,
+    particle_assets: Res<ParticleAssets>,
+    particle_effects: Res<Assets<ParticleEffects>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    material_query: Query<&Handle<StandardMaterial>, With<Idle>>,
+    mut pool: ResMut<EntityPool>,
+    accessibility: Res<Accessibility>,
+) {
+    let Some(effects) = particle_effects.get(&particle_assets.effects) else { return };
+    let mut spawner = ParticleSpawner {
+        commands: &mut commands,
+        materials: &mut materials,
+        material_query: &material_query,
+        pool: &mut pool,
+        particle_assets: &particle_assets,
+    };
+    for event in particle_burst_event_reader.read() {
+        let Some(effect) = effects.effects.get(&event.kind) else { continue };
+        if accessibility.reduced_motion && event.kind.is_flash() {
+            continue;
+        }
+        for _ in 0..effect.burst {
+            spawner.spawn(event.kind, effect, event.translation, event.direction);
+        }
+    }
+}
+
+
+fn emit_continuous_particles(
+    mut commands: Commands,
+    mut query: Query<(&mut ParticleEmitter, &GlobalTransform)>,
+    particle_assets: Res<ParticleAssets>,
+    particle_effects: Res<Assets<ParticleEffects>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    material_query: Query<&Handle<StandardMaterial>, With<Idle>>,
+    mut pool: ResMut<EntityPool>,
+    time: Res<Time>,
+) {
+    let Some(effects) = particle_effects.get(&particle_assets.effects) else { return };
+    let mut spawner = ParticleSpawner {
+        commands: &mut commands,
+        materials: &mut materials,
+        material_query: &material_query,
+        pool: &mut pool,
+        particle_assets: &particle_assets,
+    };
+    for (mut emitter, transform) in query.iter_mut() {
+        if !emitter.active {
+            emitter.accumulator = 0.;
+            continue;
+        }
+        let Some(effect) = effects.effects.get(&emitter.kind) else { continue };
+        emitter.accumulator += effect.rate * time.delta_seconds();
+        let translation = transform.transform_point(emitter.offset);
+        let direction = transform.affine().transform_vector3(emitter.direction).normalize_or_zero();
+        while emitter.accumulator >= 1. {
+            emitter.accumulator -= 1.;
+            spawner.spawn(emitter.kind, effect, translation, direction);
+        }
+    }
+}
+
+
+// Shared by the burst and continuous emitters, recycles idle particles and their materials
+struct ParticleSpawner<'a, 'w, 's> {
+    commands: &'a mut Commands<'w, 's>,
+    materials: &'a mut Assets<StandardMaterial>,
+    material_query: &'a Query<'a, 'a, &'static Handle<StandardMaterial>, With<Idle>>,
+    pool: &'a mut EntityPool,
+    particle_assets: &'a ParticleAssets,
+}
+
+
+impl ParticleSpawner<'_, '_, '_> {
+    fn spawn(&mut self, kind: ParticleEffectKind, effect: &ParticleEffect, translation: Vec3, direction: Vec3) {
+        let mut rng = rand::thread_rng();
+        let lifetime = rng.gen_range(effect.lifetime.0..=effect.lifetime.1);
+        let speed = rng.gen_range(effect.speed.0..=effect.speed.1);
+
+        // Particles stay on the play plane, so the cone is spread around the Y axis
+        let spread = effect.spread.to_radians().min(PI);
+        let base = if direction == Vec3::ZERO { Vec3::Z } else { direction };
+        let angle = if direction == Vec3::ZERO { rng.gen_range(-PI..PI) } else { rng.gen_range(-spread..=spread) };
+        let velocity = Quat::from_rotation_y(angle) * base * speed;
+
+        let particle = self.pool.acquire(self.commands, PoolKind::Particle);
+        let transform = Transform::from_translation(translation).with_scale(Vec3::splat(effect.size_at(0.)));
+        let recycled_material = self.material_query.get(particle.entity).ok()
+            .filter(|_| particle.recycled)
+            .and_then(|handle| self.materials.get_mut(handle));
+        match recycled_material {
+            Some(material) => {
+                material.base_color = effect.color_at(0.);
+                self.commands.entity(particle.entity).insert(transform);
+            },
+            None => {
+                let material = self.materials.add(StandardMaterial {
+                    base_color: effect.color_at(0.),
+                    unlit: true,
+                    alpha_mode: AlphaMode::Blend,
+                    ..default()
+                });
+                self.commands.entity(particle.entity).insert(PbrBundle {
+                    mesh: self.particle_assets.mesh.clone(),
+                    material,
+                    transform,
+                    ..default()
+                });
+            },
+        }
+        self.commands.entity(particle.entity).insert((
+            Particle { kind, velocity },
+            Lifetime::new(lifetime),
+            DisposableEntity,
+        ));
+    }
+}
+
+
+fn update_particles(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Particle, &Lifetime, &mut Transform, &Handle<StandardMaterial>), Without<Idle>>,
+    mut pool: ResMut<EntityPool>,
+    particle_assets: Res<ParticleAssets>,
+    particle_effects: Res<Assets<ParticleEffects>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+) {
+    let Some(effects) = particle_effects.get(&particle_assets.effects) else { return };
+    let dt = time.delta_seconds();
+    for (entity, mut particle, lifetime, mut transform, material) in query.iter_mut() {
+        let Some(effect) = effects.effects.get(&particle.kind) else {
+            pool.release(&mut commands, entity, PoolKind::Particle);
+            continue;
+        };
+        let velocity = particle.velocity * (1. - PARTICLE_DRAG * dt).max(0.);
+        particle.velocity = velocity;
+        transform.translation += velocity * dt;
+
+        let t = lifetime.elapsed_fraction();
+        transform.scale = Vec3::splat(effect.size_at(t));
+        if let Some(material) = materials.get_mut(material) {
+            material.base_color = effect.color_at(t);
+        }
+    }
+}
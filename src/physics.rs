@@ -1,7 +1,14 @@
-use bevy::{prelude::*, transform::TransformSystem};
+use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 
+use crate::schedule::InGameSet;
 use crate::state::GameState;
+use crate::settings::Settings;
+#[cfg(feature = "physics-debug")]
+use crate::console::{ConsoleArgs, ConsoleCommandsExt, ConsoleLog};
+
+#[cfg(feature = "physics-debug")]
+const DEBUG_RENDER_TOGGLE_KEY: KeyCode = KeyCode::F4;
 
 
 pub struct PhysicsPlugin;
@@ -12,21 +19,56 @@ impl Plugin for PhysicsPlugin {
     fn build(&self, app: &mut App) {
         let rapier_plugin = RapierPhysicsPlugin::<NoUserData>::default()
             .with_default_system_setup(false);
+        app.add_plugins(rapier_plugin);
+        // Collider outlines, for development builds only
+        #[cfg(feature = "physics-debug")]
         app
-            .add_plugins(rapier_plugin)
-            .add_plugins(RapierDebugRenderPlugin::default());
-        app.configure_sets(PostUpdate, (
+            .add_plugins(RapierDebugRenderPlugin::default())
+            .add_console_command("toggle", "toggle physics_debug - shows or hides the collider outlines", &["physics_debug"], toggle_command)
+            .add_systems(Update, toggle_debug_render);
+        // Stepped with the gameplay tick, so both advance by the same fixed amount
+        app.configure_sets(FixedUpdate, (
             PhysicsSet::SyncBackend,
             PhysicsSet::StepSimulation,
             PhysicsSet::Writeback,
-        ).chain().before(TransformSystem::TransformPropagate).run_if(in_state(GameState::InGame)));
+        ).chain().after(InGameSet::CollisionDetection).run_if(in_state(GameState::InGame)));
 
-        app.add_systems(PostUpdate, (
-            // These *must* be in the main schedule currently so that they do not miss events.
-            systems::sync_removals,
+        // This *must* be in the main schedule currently so that it does not miss events.
+        app.add_systems(PostUpdate, systems::sync_removals);
+        app.add_systems(FixedUpdate, (
             RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::SyncBackend).in_set(PhysicsSet::SyncBackend),
             RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::StepSimulation).in_set(PhysicsSet::StepSimulation),
             RapierPhysicsPlugin::<NoUserData>::get_systems(PhysicsSet::Writeback).in_set(PhysicsSet::Writeback),
         ));
+        app.add_systems(Update, apply_physics_timestep.run_if(resource_changed::<Settings>));
+    }
+}
+
+
+fn apply_physics_timestep(mut rapier_config: ResMut<RapierConfiguration>, settings: Res<Settings>) {
+    rapier_config.timestep_mode = TimestepMode::Fixed {
+        dt: settings.tick_seconds() as f32,
+        substeps: 1,
+    };
+}
+
+
+#[cfg(feature = "physics-debug")]
+fn toggle_debug_render(mut debug_render: ResMut<DebugRenderContext>, keyboard_input: Res<ButtonInput<KeyCode>>) {
+    if keyboard_input.just_pressed(DEBUG_RENDER_TOGGLE_KEY) {
+        debug_render.enabled = !debug_render.enabled;
+    }
+}
+
+
+#[cfg(feature = "physics-debug")]
+fn toggle_command(In(args): In<ConsoleArgs>, mut debug_render: ResMut<DebugRenderContext>, mut log: ResMut<ConsoleLog>) {
+    match args.first().map(String::as_str) {
+        Some("physics_debug") => {
+            debug_render.enabled = !debug_render.enabled;
+            log.info(format!("physics_debug {}", if debug_render.enabled { "on" } else { "off" }));
+        },
+        Some(name) => log.error(format!("nothing to toggle named '{}'", name)),
+        None => log.error("usage: toggle physics_debug"),
     }
 }
@@ -0,0 +1,101 @@
+use bevy::{prelude::*, utils::{HashMap, HashSet}};
+use bevy_rapier3d::prelude::*;
+
+// Idle entities kept per kind, anything released past that is despawned
+const MAX_IDLE_PER_KIND: usize = 512;
+
+
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum PoolKind {
+    Missile,
+    Particle,
+    Debris,
+}
+
+
+/// Entity that goes back to its pool instead of being despawned
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Pooled(pub PoolKind);
+
+
+/// Pooled entity waiting to be reused: hidden, and with its body and collider disabled
+#[derive(Component, Debug)]
+pub struct Idle;
+
+
+#[derive(Clone, Copy, Debug)]
+pub struct Acquired {
+    pub entity: Entity,
+    /// A recycled entity keeps its children, a fresh one is empty apart from `Pooled`
+    pub recycled: bool,
+}
+
+
+#[derive(Resource, Default, Debug)]
+pub struct EntityPool {
+    free: HashMap<PoolKind, Vec<Entity>>,
+    idle: HashSet<Entity>,
+    spawned: usize,
+    recycled: usize,
+}
+
+
+impl EntityPool {
+    /// Takes an idle entity of the kind, or spawns a new one; the caller inserts its components
+    pub fn acquire(&mut self, commands: &mut Commands, kind: PoolKind) -> Acquired {
+        while let Some(entity) = self.free.get_mut(&kind).and_then(|free| free.pop()) {
+            self.idle.remove(&entity);
+            let Some(mut entity_commands) = commands.get_entity(entity) else { continue };
+            entity_commands
+                .remove::<(Idle, RigidBodyDisabled, ColliderDisabled)>()
+                .insert(Visibility::Inherited);
+            self.recycled += 1;
+            return Acquired { entity, recycled: true };
+        }
+        self.spawned += 1;
+        Acquired { entity: commands.spawn(Pooled(kind)).id(), recycled: false }
+    }
+
+    /// Parks the entity until it is acquired again, releasing it twice is a no-op
+    pub fn release(&mut self, commands: &mut Commands, entity: Entity, kind: PoolKind) {
+        if self.idle.contains(&entity) {
+            return;
+        }
+        let Some(mut entity_commands) = commands.get_entity(entity) else { return };
+        let free = self.free.entry(kind).or_default();
+        if free.len() >= MAX_IDLE_PER_KIND {
+            entity_commands.despawn_recursive();
+            return;
+        }
+        entity_commands.insert((
+            Idle,
+            RigidBodyDisabled,
+            ColliderDisabled,
+            Velocity::zero(),
+            Visibility::Hidden,
+        ));
+        free.push(entity);
+        self.idle.insert(entity);
+    }
+
+    /// Forgets every idle entity, to be called when they get despawned by other means
+    pub fn clear(&mut self) {
+        self.free.clear();
+        self.idle.clear();
+    }
+
+    /// Number of entities spawned and recycled so far
+    pub fn stats(&self) -> (usize, usize) {
+        (self.spawned, self.recycled)
+    }
+}
+
+
+pub struct PoolPlugin;
+
+
+impl Plugin for PoolPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EntityPool>();
+    }
+}
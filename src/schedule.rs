@@ -1,8 +1,12 @@
+use std::time::Duration;
+
 use bevy::{ecs::schedule::ScheduleBuildSettings, prelude::*};
 
 use crate::state::GameState;
+use crate::settings::Settings;
 
 
+/// Gameplay steps of a fixed tick, so the outcome doesn't depend on the frame rate
 #[derive(SystemSet, Hash, PartialEq, Eq, Clone, Debug)]
 pub enum InGameSet {
     UserInput,
@@ -17,12 +21,15 @@ pub struct SchedulePlugin;
 
 impl Plugin for SchedulePlugin {
     fn build(&self, app: &mut App) {
+        // Settings are normally inserted by `main` before any plugin is built
+        let tick_seconds = app.world.get_resource::<Settings>().map_or_else(|| Settings::default().tick_seconds(), Settings::tick_seconds);
         app
+            .insert_resource(Time::<Fixed>::from_seconds(tick_seconds))
             .configure_schedules(ScheduleBuildSettings {
                 auto_insert_apply_deferred: false, // Manually configure flush points
                 ..default()
             })
-            .configure_sets(Update, (
+            .configure_sets(FixedUpdate, (
                 InGameSet::DespawnEntities,
                 // Flush point [#1] goes here
                 InGameSet::UserInput,
@@ -30,6 +37,15 @@ impl Plugin for SchedulePlugin {
                 InGameSet::CollisionDetection,
             ).chain().run_if(in_state(GameState::InGame)))
             // Insert a flush point [#1]
-            .add_systems(Update, apply_deferred.after(InGameSet::DespawnEntities).before(InGameSet::UserInput));
+            .add_systems(FixedUpdate, apply_deferred.after(InGameSet::DespawnEntities).before(InGameSet::UserInput))
+            .add_systems(Update, apply_tick_rate.run_if(resource_changed::<Settings>));
+    }
+}
+
+
+fn apply_tick_rate(mut fixed_time: ResMut<Time<Fixed>>, settings: Res<Settings>) {
+    let timestep = Duration::from_secs_f64(settings.tick_seconds());
+    if fixed_time.timestep() != timestep {
+        fixed_time.set_timestep(timestep);
     }
 }
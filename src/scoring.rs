@@ -0,0 +1,200 @@
+use bevy::prelude::*;
+
+use crate::schedule::InGameSet;
+use crate::state::GameState;
+use crate::hud::GameScoreChangeEvent;
+use crate::spaceship::Spaceship;
+use crate::collision_detection::CollisionDamageEvent;
+use crate::camera::GameCamera;
+use crate::locale::Locale;
+use crate::interpolation::interpolate_transforms;
+use crate::lifetime::Lifetime;
+use crate::accessibility::Accessibility;
+
+const COMBO_WINDOW: f32 = 2.5;
+const COMBO_STEP: f32 = 0.25;
+const COMBO_MAX_MULTIPLIER: f32 = 5.;
+// Multiplier lost per second once the combo window ran out
+const COMBO_DECAY_RATE: f32 = 1.;
+
+const LONG_RANGE_DISTANCE: f32 = 40.;
+const LONG_RANGE_BONUS: f32 = 0.5;
+const NO_DAMAGE_STREAK: u32 = 10;
+const NO_DAMAGE_BONUS: i32 = 500;
+
+const POPUP_LIFETIME: f32 = 1.;
+const POPUP_RISE: f32 = 40.;
+const POPUP_FONT_SIZE: f32 = 28.;
+
+
+/// Points awarded for destroying the entity, before multipliers
+#[derive(Component, Clone, Copy, Debug)]
+pub struct ScoreValue(pub u32);
+
+
+/// Sent by whatever destroys a target worth points
+#[derive(Event, Debug)]
+pub struct TargetDestroyedEvent {
+    pub points: u32,
+    pub translation: Vec3,
+}
+
+
+#[derive(Resource, Debug)]
+pub struct Combo {
+    pub multiplier: f32,
+    /// Time left to chain the next kill
+    pub timer: Timer,
+    kills_without_damage: u32,
+}
+
+
+impl Default for Combo {
+    fn default() -> Self {
+        let mut timer = Timer::from_seconds(COMBO_WINDOW, TimerMode::Once);
+        timer.tick(timer.duration());
+        Self { multiplier: 1., timer, kills_without_damage: 0 }
+    }
+}
+
+
+impl Combo {
+    pub fn is_active(&self) -> bool {
+        !self.timer.finished()
+    }
+}
+
+
+#[derive(Component, Debug)]
+struct ScorePopup {
+    translation: Vec3,
+}
+
+
+pub struct ScoringPlugin;
+
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_resource::<Combo>()
+            .add_event::<TargetDestroyedEvent>()
+            .add_systems(FixedUpdate, (update_combo, reset_no_damage_streak, award_points)
+                .chain()
+                .in_set(InGameSet::EntityUpdates))
+            .add_systems(Update, update_score_popups.after(interpolate_transforms).run_if(in_state(GameState::InGame)))
+            .add_systems(OnEnter(GameState::GameOver), (reset_combo, despawn_score_popups));
+    }
+}
+
+
+fn update_combo(mut combo: ResMut<Combo>, time: Res<Time>) {
+    combo.timer.tick(time.delta());
+    if !combo.is_active() {
+        combo.multiplier = (combo.multiplier - COMBO_DECAY_RATE * time.delta_seconds()).max(1.);
+    }
+}
+
+
+fn reset_no_damage_streak(
+    mut collision_damage_event_reader: EventReader<CollisionDamageEvent>,
+    mut combo: ResMut<Combo>,
+    query: Query<(), With<Spaceship>>,
+) {
+    for event in collision_damage_event_reader.read() {
+        if query.contains(event.entity) {
+            combo.kills_without_damage = 0;
+        }
+    }
+}
+
+
+fn award_points(
+    mut commands: Commands,
+    mut target_destroyed_event_reader: EventReader<TargetDestroyedEvent>,
+    mut score_change_event_writer: EventWriter<GameScoreChangeEvent>,
+    mut combo: ResMut<Combo>,
+    ship_query: Query<&GlobalTransform, With<Spaceship>>,
+    locale: Res<Locale>,
+    accessibility: Res<Accessibility>,
+) {
+    let palette = accessibility.colors();
+    let ship = ship_query.get_single().ok().map(|transform| transform.translation());
+    for &TargetDestroyedEvent { points, translation } in target_destroyed_event_reader.read() {
+        if combo.is_active() {
+            combo.multiplier = (combo.multiplier + COMBO_STEP).min(COMBO_MAX_MULTIPLIER);
+        }
+        combo.timer.reset();
+
+        let long_range = ship.map_or(false, |ship| ship.distance(translation) > LONG_RANGE_DISTANCE);
+        let range_scalar = if long_range { 1. + LONG_RANGE_BONUS } else { 1. };
+        let score = (points as f32 * combo.multiplier * range_scalar).round() as i32;
+        score_change_event_writer.send(GameScoreChangeEvent { score_delta: score, clear_score: false });
+        let key = if long_range { "popup-long-shot" } else { "popup-points" };
+        let label = locale.format(key, &[("points", locale.group_digits(score as i64).into())]);
+        spawn_score_popup(&mut commands, label, palette.popup, translation);
+
+        combo.kills_without_damage += 1;
+        if combo.kills_without_damage % NO_DAMAGE_STREAK == 0 {
+            score_change_event_writer.send(GameScoreChangeEvent { score_delta: NO_DAMAGE_BONUS, clear_score: false });
+            let label = locale.format("popup-no-damage-streak", &[("points", locale.group_digits(NO_DAMAGE_BONUS as i64).into())]);
+            spawn_score_popup(&mut commands, label, palette.bonus_popup, translation);
+        }
+    }
+}
+
+
+fn spawn_score_popup(commands: &mut Commands, label: String, color: Color, translation: Vec3) {
+    commands.spawn((
+        TextBundle {
+            // Hidden until it is placed over its world position
+            visibility: Visibility::Hidden,
+            ..TextBundle::from_section(label, TextStyle {
+                font_size: POPUP_FONT_SIZE,
+                color,
+                ..default()
+            }).with_style(Style {
+                position_type: PositionType::Absolute,
+                ..default()
+            })
+        },
+        ScorePopup { translation },
+        Lifetime::new(POPUP_LIFETIME),
+    ));
+}
+
+
+// Popups follow their world position on screen while rising and fading out
+fn update_score_popups(
+    mut query: Query<(&ScorePopup, &Lifetime, &mut Style, &mut Text, &mut Visibility)>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<GameCamera>>,
+) {
+    let Ok((camera, camera_transform)) = camera_query.get_single() else { return };
+    for (popup, lifetime, mut style, mut text, mut visibility) in query.iter_mut() {
+        let t = lifetime.elapsed_fraction();
+        let Some(position) = camera.world_to_viewport(camera_transform, popup.translation) else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        style.left = Val::Px(position.x);
+        style.top = Val::Px(position.y - POPUP_RISE * t);
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(1. - t * t);
+        }
+    }
+}
+
+
+fn reset_combo(mut combo: ResMut<Combo>) {
+    *combo = Combo::default();
+}
+
+
+fn despawn_score_popups(mut commands: Commands, query: Query<Entity, With<ScorePopup>>) {
+    for entity in query.iter() {
+        if let Some(entity_commands) = commands.get_entity(entity) {
+            entity_commands.despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,514 @@
+use std::{fs, path::PathBuf};
+
+use bevy::{
+    audio::Volume,
+    prelude::*,
+    window::{PresentMode, PrimaryWindow, WindowMode, WindowResolution},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::accessibility::Accessibility;
+use crate::locale::{Locale, DEFAULT_LANGUAGE};
+
+const WINDOW_TITLE: &'static str = "tds-proto";
+const CONFIG_DIR_NAME: &'static str = "tds-proto";
+const SETTINGS_FILE_NAME: &'static str = "settings.ron";
+
+const RESOLUTIONS: &'static [(u32, u32)] = &[
+    (1280, 720),
+    (1600, 900),
+    (1920, 1080),
+    (2560, 1440),
+];
+// Master volume goes from silent to full in this many steps
+const VOLUME_STEPS: u32 = 10;
+const HUD_SCALE_RANGE: (f32, f32) = (0.75, 1.5);
+const HUD_SCALE_STEP: f32 = 0.25;
+const BACKGROUND_DENSITIES: &'static [f32] = &[0.25, 0.5, 1.];
+const RADAR_RANGES: &'static [f32] = &[50., 100., 150., 200.];
+const RADAR_SCALES: &'static [f32] = &[0.75, 1., 1.25, 1.5];
+const TICK_RATE_RANGE: (u32, u32) = (20, 240);
+
+
+#[derive(Serialize, Deserialize, Default, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum WindowModeSetting {
+    #[default]
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+
+impl WindowModeSetting {
+    fn next(self) -> Self {
+        match self {
+            Self::Windowed => Self::Borderless,
+            Self::Borderless => Self::Fullscreen,
+            Self::Fullscreen => Self::Windowed,
+        }
+    }
+
+    fn label_key(self) -> &'static str {
+        match self {
+            Self::Windowed => "window-mode-windowed",
+            Self::Borderless => "window-mode-borderless",
+            Self::Fullscreen => "window-mode-fullscreen",
+        }
+    }
+
+    pub fn window_mode(self) -> WindowMode {
+        match self {
+            Self::Windowed => WindowMode::Windowed,
+            Self::Borderless => WindowMode::BorderlessFullscreen,
+            Self::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+
+#[derive(Serialize, Deserialize, Default, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+
+impl Difficulty {
+    fn next(self) -> Self {
+        match self {
+            Self::Easy => Self::Normal,
+            Self::Normal => Self::Hard,
+            Self::Hard => Self::Easy,
+        }
+    }
+
+    fn label_key(self) -> &'static str {
+        match self {
+            Self::Easy => "difficulty-easy",
+            Self::Normal => "difficulty-normal",
+            Self::Hard => "difficulty-hard",
+        }
+    }
+
+    /// Multiplier applied to the asteroid spawn interval
+    pub fn spawn_interval_scale(self) -> f32 {
+        match self {
+            Self::Easy => 1.5,
+            Self::Normal => 1.,
+            Self::Hard => 0.6,
+        }
+    }
+}
+
+
+#[derive(Serialize, Deserialize, Default, Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CameraMode {
+    /// Overview of the whole arena from a fixed point
+    #[default]
+    Fixed,
+    /// Follows the ship once it leaves a dead zone around the screen center
+    Follow,
+    /// Follows the ship, leading ahead in the direction it is moving
+    LookAhead,
+}
+
+
+impl CameraMode {
+    fn next(self) -> Self {
+        match self {
+            Self::Fixed => Self::Follow,
+            Self::Follow => Self::LookAhead,
+            Self::LookAhead => Self::Fixed,
+        }
+    }
+
+    fn label_key(self) -> &'static str {
+        match self {
+            Self::Fixed => "camera-mode-fixed",
+            Self::Follow => "camera-mode-follow",
+            Self::LookAhead => "camera-mode-look-ahead",
+        }
+    }
+}
+
+
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum KeyAction {
+    Forward,
+    Backward,
+    TurnLeft,
+    TurnRight,
+    RollLeft,
+    RollRight,
+    Fire,
+}
+
+
+impl KeyAction {
+    pub const ALL: [KeyAction; 7] = [
+        KeyAction::Forward,
+        KeyAction::Backward,
+        KeyAction::TurnLeft,
+        KeyAction::TurnRight,
+        KeyAction::RollLeft,
+        KeyAction::RollRight,
+        KeyAction::Fire,
+    ];
+
+    /// Message key of the action's name
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Forward => "action-forward",
+            Self::Backward => "action-backward",
+            Self::TurnLeft => "action-turn-left",
+            Self::TurnRight => "action-turn-right",
+            Self::RollLeft => "action-roll-left",
+            Self::RollRight => "action-roll-right",
+            Self::Fire => "action-fire",
+        }
+    }
+}
+
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub forward: KeyCode,
+    pub backward: KeyCode,
+    pub turn_left: KeyCode,
+    pub turn_right: KeyCode,
+    pub roll_left: KeyCode,
+    pub roll_right: KeyCode,
+    pub fire: KeyCode,
+}
+
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            forward: KeyCode::KeyW,
+            backward: KeyCode::KeyS,
+            turn_left: KeyCode::KeyA,
+            turn_right: KeyCode::KeyD,
+            roll_left: KeyCode::KeyQ,
+            roll_right: KeyCode::KeyE,
+            fire: KeyCode::Space,
+        }
+    }
+}
+
+
+impl KeyBindings {
+    pub fn get(&self, action: KeyAction) -> KeyCode {
+        match action {
+            KeyAction::Forward => self.forward,
+            KeyAction::Backward => self.backward,
+            KeyAction::TurnLeft => self.turn_left,
+            KeyAction::TurnRight => self.turn_right,
+            KeyAction::RollLeft => self.roll_left,
+            KeyAction::RollRight => self.roll_right,
+            KeyAction::Fire => self.fire,
+        }
+    }
+
+    pub fn set(&mut self, action: KeyAction, key: KeyCode) {
+        let binding = match action {
+            KeyAction::Forward => &mut self.forward,
+            KeyAction::Backward => &mut self.backward,
+            KeyAction::TurnLeft => &mut self.turn_left,
+            KeyAction::TurnRight => &mut self.turn_right,
+            KeyAction::RollLeft => &mut self.roll_left,
+            KeyAction::RollRight => &mut self.roll_right,
+            KeyAction::Fire => &mut self.fire,
+        };
+        *binding = key;
+    }
+}
+
+
+/// Every user-tweakable setting shown on the settings screen
+#[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SettingKind {
+    Language,
+    WindowMode,
+    Resolution,
+    Vsync,
+    MasterVolume,
+    Difficulty,
+    HudScale,
+    CameraMode,
+    BackgroundDensity,
+    RadarRange,
+    RadarScale,
+    UiScale,
+    ColorPalette,
+    ReducedMotion,
+    FireMode,
+    GameSpeed,
+    KeyBinding(KeyAction),
+}
+
+
+impl SettingKind {
+    pub const GENERAL: [SettingKind; 11] = [
+        SettingKind::Language,
+        SettingKind::WindowMode,
+        SettingKind::Resolution,
+        SettingKind::Vsync,
+        SettingKind::MasterVolume,
+        SettingKind::Difficulty,
+        SettingKind::HudScale,
+        SettingKind::CameraMode,
+        SettingKind::BackgroundDensity,
+        SettingKind::RadarRange,
+        SettingKind::RadarScale,
+    ];
+
+    pub const ACCESSIBILITY: [SettingKind; 5] = [
+        SettingKind::UiScale,
+        SettingKind::ColorPalette,
+        SettingKind::ReducedMotion,
+        SettingKind::FireMode,
+        SettingKind::GameSpeed,
+    ];
+
+    /// Message key of the setting's name
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Language => "setting-language",
+            Self::WindowMode => "setting-window-mode",
+            Self::Resolution => "setting-resolution",
+            Self::Vsync => "setting-vsync",
+            Self::MasterVolume => "setting-master-volume",
+            Self::Difficulty => "setting-difficulty",
+            Self::HudScale => "setting-hud-scale",
+            Self::CameraMode => "setting-camera-mode",
+            Self::BackgroundDensity => "setting-background-density",
+            Self::RadarRange => "setting-radar-range",
+            Self::RadarScale => "setting-radar-scale",
+            Self::UiScale => "setting-ui-scale",
+            Self::ColorPalette => "setting-color-palette",
+            Self::ReducedMotion => "setting-reduced-motion",
+            Self::FireMode => "setting-fire-mode",
+            Self::GameSpeed => "setting-game-speed",
+            Self::KeyBinding(action) => action.label_key(),
+        }
+    }
+}
+
+
+#[derive(Resource, Serialize, Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct Settings {
+    /// Identifier of one of the locales under `assets/locales`
+    pub language: String,
+    pub window_mode: WindowModeSetting,
+    pub resolution: (u32, u32),
+    pub vsync: bool,
+    /// Out of `VOLUME_STEPS`, see `master_volume()`
+    pub master_volume_steps: u32,
+    pub key_bindings: KeyBindings,
+    pub difficulty: Difficulty,
+    pub hud_scale: f32,
+    pub camera_mode: CameraMode,
+    /// Fraction of the full background detail, lower is lighter on the GPU
+    pub background_density: f32,
+    /// World units covered from the center to the edge of the radar
+    pub radar_range: f32,
+    pub radar_scale: f32,
+    pub accessibility: Accessibility,
+    /// Simulation steps per second, independent of the frame rate; only set in the settings file
+    pub tick_rate: u32,
+    /// Manifest name of the hull picked in the hangar
+    pub selected_hull: Option<String>,
+}
+
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            language: DEFAULT_LANGUAGE.to_string(),
+            window_mode: WindowModeSetting::default(),
+            resolution: RESOLUTIONS[0],
+            vsync: true,
+            master_volume_steps: VOLUME_STEPS,
+            key_bindings: KeyBindings::default(),
+            difficulty: Difficulty::default(),
+            hud_scale: 1.,
+            camera_mode: CameraMode::default(),
+            background_density: 1.,
+            radar_range: RADAR_RANGES[1],
+            radar_scale: 1.,
+            accessibility: Accessibility::default(),
+            tick_rate: 60,
+            selected_hull: None,
+        }
+    }
+}
+
+
+impl Settings {
+    fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(SETTINGS_FILE_NAME))
+    }
+
+    /// Reads the settings file from the user's config directory.
+    /// Runs before the app (and its logger) exists, so problems go straight to stderr.
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else { return Self::default() };
+        let Ok(contents) = fs::read_to_string(&path) else { return Self::default() };
+        let mut settings: Self = ron::from_str(&contents).unwrap_or_else(|err| {
+            eprintln!("[SETTINGS] Failed to parse {}: {}", path.display(), err);
+            Self::default()
+        });
+        settings.accessibility.snap_to_options();
+        settings
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::path() else { return };
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+                }
+                fs::write(&path, contents).map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            error!("Failed to save settings to {}: {}", path.display(), err);
+        }
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync { PresentMode::AutoVsync } else { PresentMode::AutoNoVsync }
+    }
+
+    /// Window description for `WindowPlugin`, so the window is created with the stored settings
+    pub fn primary_window(&self) -> Window {
+        Window {
+            title: WINDOW_TITLE.to_string(),
+            mode: self.window_mode.window_mode(),
+            resolution: WindowResolution::new(self.resolution.0 as f32, self.resolution.1 as f32),
+            present_mode: self.present_mode(),
+            ..default()
+        }
+    }
+
+    /// Seconds simulated by one fixed tick
+    pub fn tick_seconds(&self) -> f64 {
+        1. / self.tick_rate.clamp(TICK_RATE_RANGE.0, TICK_RATE_RANGE.1) as f64
+    }
+
+    /// Master volume from 0 to 1
+    pub fn master_volume(&self) -> f32 {
+        self.master_volume_steps.min(VOLUME_STEPS) as f32 / VOLUME_STEPS as f32
+    }
+
+    /// Moves a setting to its next value, wrapping around at the end of the range
+    pub fn cycle(&mut self, kind: SettingKind) {
+        match kind {
+            SettingKind::Language => {
+                let languages: Vec<&str> = Locale::languages().collect();
+                let index = languages.iter().position(|&language| language == self.language).map_or(0, |i| i + 1);
+                self.language = languages[index % languages.len()].to_string();
+            },
+            SettingKind::WindowMode => self.window_mode = self.window_mode.next(),
+            SettingKind::Resolution => {
+                let index = RESOLUTIONS.iter().position(|&res| res == self.resolution).map_or(0, |i| i + 1);
+                self.resolution = RESOLUTIONS[index % RESOLUTIONS.len()];
+            },
+            SettingKind::Vsync => self.vsync = !self.vsync,
+            SettingKind::MasterVolume => self.master_volume_steps = (self.master_volume_steps.min(VOLUME_STEPS) + 1) % (VOLUME_STEPS + 1),
+            SettingKind::Difficulty => self.difficulty = self.difficulty.next(),
+            SettingKind::HudScale => {
+                let scale = self.hud_scale + HUD_SCALE_STEP;
+                self.hud_scale = if scale > HUD_SCALE_RANGE.1 + f32::EPSILON { HUD_SCALE_RANGE.0 } else { scale };
+            },
+            SettingKind::CameraMode => self.camera_mode = self.camera_mode.next(),
+            SettingKind::BackgroundDensity => {
+                let index = BACKGROUND_DENSITIES.iter().position(|&density| density == self.background_density).map_or(0, |i| i + 1);
+                self.background_density = BACKGROUND_DENSITIES[index % BACKGROUND_DENSITIES.len()];
+            },
+            SettingKind::RadarRange => {
+                let index = RADAR_RANGES.iter().position(|&range| range == self.radar_range).map_or(0, |i| i + 1);
+                self.radar_range = RADAR_RANGES[index % RADAR_RANGES.len()];
+            },
+            SettingKind::RadarScale => {
+                let index = RADAR_SCALES.iter().position(|&scale| scale == self.radar_scale).map_or(0, |i| i + 1);
+                self.radar_scale = RADAR_SCALES[index % RADAR_SCALES.len()];
+            },
+            SettingKind::UiScale => self.accessibility.next_ui_scale(),
+            SettingKind::ColorPalette => self.accessibility.next_palette(),
+            SettingKind::ReducedMotion => self.accessibility.reduced_motion = !self.accessibility.reduced_motion,
+            SettingKind::FireMode => self.accessibility.next_fire_mode(),
+            SettingKind::GameSpeed => self.accessibility.next_game_speed(),
+            SettingKind::KeyBinding(_) => (),  // Rebinding waits for a key press instead
+        }
+    }
+
+    pub fn describe(&self, kind: SettingKind, locale: &Locale) -> String {
+        match kind {
+            SettingKind::Language => locale.text("language-name"),
+            SettingKind::WindowMode => locale.text(self.window_mode.label_key()),
+            SettingKind::Resolution => format!("{}x{}", self.resolution.0, self.resolution.1),
+            SettingKind::Vsync => locale.text(if self.vsync { "value-on" } else { "value-off" }),
+            SettingKind::MasterVolume => format_percent(self.master_volume()),
+            SettingKind::Difficulty => locale.text(self.difficulty.label_key()),
+            SettingKind::HudScale => format!("{}x", locale.decimal(self.hud_scale, 2)),
+            SettingKind::CameraMode => locale.text(self.camera_mode.label_key()),
+            SettingKind::BackgroundDensity => format_percent(self.background_density),
+            SettingKind::RadarRange => format!("{:.0}", self.radar_range),
+            SettingKind::RadarScale => format!("{}x", locale.decimal(self.radar_scale, 2)),
+            SettingKind::UiScale => format!("{}x", locale.decimal(self.accessibility.ui_scale, 2)),
+            SettingKind::ColorPalette => locale.text(self.accessibility.palette.label_key()),
+            SettingKind::ReducedMotion => locale.text(if self.accessibility.reduced_motion { "value-on" } else { "value-off" }),
+            SettingKind::FireMode => locale.text(self.accessibility.fire_mode.label_key()),
+            SettingKind::GameSpeed => format_percent(self.accessibility.game_speed),
+            SettingKind::KeyBinding(action) => format!("{:?}", self.key_bindings.get(action)),
+        }
+    }
+}
+
+
+fn format_percent(fraction: f32) -> String {
+    format!("{}%", (fraction * 100.).round() as i32)
+}
+
+
+pub struct SettingsPlugin;
+
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // Normally inserted by `main` before the window is created
+            .init_resource::<Settings>()
+            .add_systems(Update, apply_settings.run_if(resource_changed::<Settings>));
+    }
+}
+
+
+fn apply_settings(
+    settings: Res<Settings>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    mut global_volume: ResMut<GlobalVolume>,
+) {
+    global_volume.volume = Volume::new(settings.master_volume());
+
+    let Ok(mut window) = window_query.get_single_mut() else { return };
+    let mode = settings.window_mode.window_mode();
+    if window.mode != mode {
+        window.mode = mode;
+    }
+    let (width, height) = (settings.resolution.0 as f32, settings.resolution.1 as f32);
+    if window.resolution.width() != width || window.resolution.height() != height {
+        window.resolution.set(width, height);
+    }
+    let present_mode = settings.present_mode();
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+}
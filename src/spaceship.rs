@@ -1,29 +1,106 @@
+use std::f32::consts::TAU;
+
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
 
 use crate::schedule::InGameSet;
 use crate::state::GameState;
-use crate::asset_loader::SceneAssets;
+use crate::asset_loader::{ModelAsset, SceneAssets};
 use crate::movement::{DirVector, MovingObjectBundle};
-use crate::health::Health;
-use crate::collision_detection::CollisionDamage;
-use crate::despawn_routine::DisposableEntity;
+use crate::interpolation::InterpolatedTransform;
+use crate::health::{Health, Invulnerable};
+use crate::collision_detection::{apply_collision_damage, CollisionDamage};
+use crate::despawn_routine::{DespawnPolicy, DisposableEntity};
+use crate::settings::Settings;
+use crate::accessibility::{Accessibility, FireMode};
+use crate::particles::{ParticleBurstEvent, ParticleEffectKind, ParticleEmitter};
+use crate::pool::{EntityPool, PoolKind};
+use crate::stats::StatEvent;
+use crate::lifetime::{tick_lifetimes, Lifetime, LifetimeExpiredEvent, LifetimeFade};
+use crate::console::{parse_arg, ConsoleArgs, ConsoleCommandsExt, ConsoleLog};
+use crate::hazards::WeaponsDisabled;
 
 const SPACESHIP_SPAWN: Vec3 = Vec3::new(0., 0., -20.);
 const SPACESHIP_SPEED: f32 = 25.;
 const SPACESHIP_ROTATION_SPEED: f32 = 2.5;
 const SPACESHIP_ROLL_SPEED: f32 = 2.5;
-const SPACESHIP_RADIUS: f32 = 4.;
 const SPACESHIP_HEALTH: f32 = 100.;
 const SPACESHIP_COLLISION_DAMAGE: f32 = 70.;
+const SPACESHIP_EXHAUST_OFFSET: Vec3 = Vec3::new(0., 0., -4.);
 
 const MISSILE_SPEED: f32 = 50.;
 const MISSILE_FORWARD_SCALAR: f32 = 8.;
-const MISSILE_RADIUS: f32 = 0.2;
 const MISSILE_COOLDOWN: f32 = 0.2;
-const MISSILE_SCALE: Vec3 = Vec3::new(5., 5., 5.);
 const MISSILE_HEALTH: f32 = 1.;
 const MISSILE_COLLISION_DAMAGE: f32 = 40.;
+const MISSILE_LIFETIME: f32 = 2.;
+const MISSILE_SHRINK_FRACTION: f32 = 0.2;
+
+const FLAK_FRAGMENT_LIFETIME: f32 = 0.4;
+const FLAK_FRAGMENT_SPEED_SCALAR: f32 = 0.6;
+const FLAK_FRAGMENT_DAMAGE_SCALAR: f32 = 0.5;
+
+
+/// Per-hull handling and loadout, listed next to the model in the manifest
+#[derive(Component, Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct HullStats {
+    pub display_name: String,
+    pub speed: f32,
+    pub turn_rate: f32,
+    pub roll_rate: f32,
+    pub health: f32,
+    pub collision_damage: f32,
+    pub weapon: WeaponStats,
+}
+
+
+impl Default for HullStats {
+    fn default() -> Self {
+        Self {
+            display_name: String::new(),
+            speed: SPACESHIP_SPEED,
+            turn_rate: SPACESHIP_ROTATION_SPEED,
+            roll_rate: SPACESHIP_ROLL_SPEED,
+            health: SPACESHIP_HEALTH,
+            collision_damage: SPACESHIP_COLLISION_DAMAGE,
+            weapon: WeaponStats::default(),
+        }
+    }
+}
+
+
+#[derive(Deserialize, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct WeaponStats {
+    pub cooldown: f32,
+    pub damage: f32,
+    pub missile_speed: f32,
+    /// Seconds before a missile that hit nothing expires
+    pub lifetime: f32,
+    /// Fragments a missile bursts into when it expires, 0 for none
+    pub flak: u32,
+    /// Missiles fired per volley
+    pub count: u32,
+    /// Angle in degrees between neighbouring missiles of a volley
+    pub spread: f32,
+}
+
+
+impl Default for WeaponStats {
+    fn default() -> Self {
+        Self {
+            cooldown: MISSILE_COOLDOWN,
+            damage: MISSILE_COLLISION_DAMAGE,
+            missile_speed: MISSILE_SPEED,
+            lifetime: MISSILE_LIFETIME,
+            flak: 0,
+            count: 1,
+            spread: 0.,
+        }
+    }
+}
 
 
 #[derive(Component, Debug)]
@@ -34,6 +111,33 @@ pub struct Spaceship;
 pub struct SpaceshipMissile;
 
 
+/// Slows the ship down to a fraction of its speed until the timer runs out
+#[derive(Component, Debug)]
+pub struct Chilled {
+    speed_factor: f32,
+    timer: Timer,
+}
+
+
+impl Chilled {
+    pub fn new(speed_factor: f32, seconds: f32) -> Self {
+        Self {
+            speed_factor: speed_factor.clamp(0., 1.),
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
+        }
+    }
+}
+
+
+// Missile that bursts into fragments once its lifetime runs out
+#[derive(Component, Debug)]
+struct FlakWarhead {
+    fragments: u32,
+    damage: f32,
+    speed: f32,
+}
+
+
 #[derive(Resource, Default, Debug)]
 struct SpaceshipMissileCooldownTimer {
     timer: Timer,
@@ -43,12 +147,17 @@ struct SpaceshipMissileCooldownTimer {
 impl SpaceshipMissileCooldownTimer {
     fn new(seconds: f32) -> Self {
         Self {
-            timer: Timer::from_seconds(seconds, TimerMode::Repeating),
+            timer: Timer::from_seconds(seconds, TimerMode::Once),
         }
     }
 }
 
 
+// Whether the weapon keeps firing in `FireMode::Toggle`
+#[derive(Resource, Default, Debug)]
+struct FireToggle(bool);
+
+
 pub struct SpaceshipPlugin;
 
 
@@ -56,69 +165,98 @@ impl Plugin for SpaceshipPlugin {
     fn build(&self, app: &mut App) {
         app
             .insert_resource(SpaceshipMissileCooldownTimer::new(MISSILE_COOLDOWN))
-            .add_systems(Update, (
+            .init_resource::<FireToggle>()
+            .add_console_command("god", "god - toggles invulnerability of the ship", &[], god_command)
+            .add_console_command("set", "set health <v> - sets the health of the ship", &["health"], set_command)
+            .add_systems(Update, toggle_fire.run_if(in_state(GameState::InGame)))
+            .add_systems(FixedUpdate, (
                 spaceship_movement_controls,
                 spaceship_weapon_controls,
             ).chain().in_set(InGameSet::UserInput))
-            .add_systems(Update, check_spaceship.in_set(InGameSet::EntityUpdates))
+            .add_systems(FixedUpdate, (
+                check_spaceship,
+                thaw_spaceship,
+                burst_flak_missiles.after(tick_lifetimes),
+            ).in_set(InGameSet::EntityUpdates))
+            .add_systems(FixedUpdate, (
+                spaceship_destruction_effects,
+                count_missile_hits,
+            ).after(apply_collision_damage).in_set(InGameSet::CollisionDetection))
             .add_systems(OnExit(GameState::Menu), spawn_spaceship)
             .add_systems(OnEnter(GameState::GameOver), spawn_spaceship);
     }
 }
 
 
-fn spawn_spaceship(mut commands: Commands, scene_assets: Res<SceneAssets>) {
+fn spawn_spaceship(
+    mut commands: Commands,
+    scene_assets: Res<SceneAssets>,
+    settings: Res<Settings>,
+    mut cd_timer: ResMut<SpaceshipMissileCooldownTimer>,
+    mut fire_toggle: ResMut<FireToggle>,
+) {
+    fire_toggle.0 = false;
+    let model = scene_assets.get_spaceship(settings.selected_hull.as_deref());
+    let hull = model.hull.clone().unwrap_or_default();
+    *cd_timer = SpaceshipMissileCooldownTimer::new(hull.weapon.cooldown);
+
     let spaceship_transform = DirVector::new(SPACESHIP_SPAWN).get_transform();
     commands.spawn((
         MovingObjectBundle {
             velocity: Velocity::linear(Vec3::ZERO),
             rigid_body: RigidBody::KinematicPositionBased,
-            collider: Collider::ball(SPACESHIP_RADIUS),
-            ..default()
-        },
-        SceneBundle {
-            scene: scene_assets.get_random_spaceship(),
-            transform: spaceship_transform,
+            collider: model.collider(),
             ..default()
         },
+        SpatialBundle::from_transform(model.transform(spaceship_transform)),
         Spaceship,
-        Health::new(SPACESHIP_HEALTH),
-        CollisionDamage::new(SPACESHIP_COLLISION_DAMAGE),
+        ParticleEmitter::new(ParticleEffectKind::EngineExhaust, SPACESHIP_EXHAUST_OFFSET, Vec3::NEG_Z),
+        Health::new(hull.health),
+        CollisionDamage::new(hull.collision_damage),
+        hull,
         DisposableEntity,
-    ));
+        // The camera may not be following the ship, so it is only lost when leaving the arena
+        DespawnPolicy::OutsideArena,
+    )).with_children(|parent| {
+        parent.spawn(model.scene_bundle());
+    });
 }
 
 
 fn spaceship_movement_controls(
-    mut query: Query<&mut Transform, With<Spaceship>>,
+    mut query: Query<(&mut Transform, &mut ParticleEmitter, &HullStats, Option<&Chilled>), With<Spaceship>>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
     time: Res<Time>,
 ) {
-    let Ok(mut transform) = query.get_single_mut() else { return };
+    let Ok((mut transform, mut exhaust, hull, chilled)) = query.get_single_mut() else { return };
+    let bindings = &settings.key_bindings;
+    let speed = hull.speed * chilled.map_or(1., |chilled| chilled.speed_factor);
 
     let mut rotation = 0.;
-    if keyboard_input.pressed(KeyCode::KeyD) {
-        rotation -= SPACESHIP_ROTATION_SPEED * time.delta_seconds();
+    if keyboard_input.pressed(bindings.turn_right) {
+        rotation -= hull.turn_rate * time.delta_seconds();
     }
-    if keyboard_input.pressed(KeyCode::KeyA) {
-        rotation += SPACESHIP_ROTATION_SPEED * time.delta_seconds();
+    if keyboard_input.pressed(bindings.turn_left) {
+        rotation += hull.turn_rate * time.delta_seconds();
     }
 
     let mut roll = 0.;
-    if keyboard_input.pressed(KeyCode::KeyQ) {
-        roll -= SPACESHIP_ROLL_SPEED * time.delta_seconds();
+    if keyboard_input.pressed(bindings.roll_left) {
+        roll -= hull.roll_rate * time.delta_seconds();
     }
-    if keyboard_input.pressed(KeyCode::KeyE) {
-        roll += SPACESHIP_ROLL_SPEED * time.delta_seconds();
+    if keyboard_input.pressed(bindings.roll_right) {
+        roll += hull.roll_rate * time.delta_seconds();
     }
 
     let mut movement = 0.;
-    if keyboard_input.pressed(KeyCode::KeyS) {
-        movement -= SPACESHIP_SPEED * time.delta_seconds();
+    if keyboard_input.pressed(bindings.backward) {
+        movement -= speed * time.delta_seconds();
     }
-    if keyboard_input.pressed(KeyCode::KeyW) {
-        movement += SPACESHIP_SPEED * time.delta_seconds();
+    if keyboard_input.pressed(bindings.forward) {
+        movement += speed * time.delta_seconds();
     }
+    exhaust.active = keyboard_input.pressed(bindings.forward);
 
     // Modify transform based on the processed input
     // NOTE: negative forward cause of the model direction
@@ -130,42 +268,145 @@ fn spaceship_movement_controls(
 }
 
 
+// Presses are read every frame, a tick could miss them or see them twice
+fn toggle_fire(
+    mut fire_toggle: ResMut<FireToggle>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    accessibility: Res<Accessibility>,
+) {
+    if accessibility.fire_mode == FireMode::Toggle && keyboard_input.just_pressed(settings.key_bindings.fire) {
+        fire_toggle.0 = !fire_toggle.0;
+    }
+}
+
+
 fn spaceship_weapon_controls(
     mut commands: Commands,
-    query: Query<&Transform, With<Spaceship>>,
+    query: Query<(&Transform, &HullStats), (With<Spaceship>, Without<WeaponsDisabled>)>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    accessibility: Res<Accessibility>,
+    fire_toggle: Res<FireToggle>,
     mut cd_timer: ResMut<SpaceshipMissileCooldownTimer>,
+    mut pool: ResMut<EntityPool>,
     scene_assets: Res<SceneAssets>,
+    mut stat_event_writer: EventWriter<StatEvent>,
     time: Res<Time>,
 ) {
-    if !keyboard_input.pressed(KeyCode::Space) {
-        return;
-    }
-
+    // The cooldown runs down whether or not the weapon is firing
     cd_timer.timer.tick(time.delta());
-    if !cd_timer.timer.just_finished() {
+    let firing = match accessibility.fire_mode {
+        FireMode::Hold => keyboard_input.pressed(settings.key_bindings.fire),
+        FireMode::Toggle => fire_toggle.0,
+    };
+    if !firing || !cd_timer.timer.finished() {
         return;
     }
+    cd_timer.timer.reset();
 
-    let Ok(transform) = query.get_single() else { return };
+    let Ok((transform, hull)) = query.get_single() else { return };
+    let weapon = &hull.weapon;
+    let model = scene_assets.get_random_bullet();
     let missile_transform = DirVector::new(transform.translation - transform.forward() * MISSILE_FORWARD_SCALAR)
-        .get_transform().with_scale(MISSILE_SCALE);
-    commands.spawn((
-        MovingObjectBundle {
-            velocity: Velocity::linear(-transform.forward() * MISSILE_SPEED),
-            collider: Collider::ball(MISSILE_RADIUS),
-            ..default()
-        },
-        SceneBundle {
-            scene: scene_assets.get_random_bullet(),
-            transform: missile_transform,
-            ..default()
-        },
+        .get_transform();
+
+    stat_event_writer.send(StatEvent::ShotsFired(weapon.count));
+
+    // Volleys fan out symmetrically around the ship's heading
+    for i in 0..weapon.count {
+        let angle = (i as f32 - (weapon.count - 1) as f32 / 2.) * weapon.spread.to_radians();
+        let direction = Quat::from_rotation_y(angle) * -transform.forward();
+        let missile = spawn_missile(
+            &mut commands,
+            &mut pool,
+            model,
+            model.transform(missile_transform),
+            direction * weapon.missile_speed,
+            weapon.damage,
+            weapon.lifetime,
+        );
+        if weapon.flak > 0 {
+            commands.entity(missile).insert(FlakWarhead {
+                fragments: weapon.flak,
+                damage: weapon.damage * FLAK_FRAGMENT_DAMAGE_SCALAR,
+                speed: weapon.missile_speed * FLAK_FRAGMENT_SPEED_SCALAR,
+            });
+        }
+    }
+}
+
+
+fn spawn_missile(
+    commands: &mut Commands,
+    pool: &mut EntityPool,
+    model: &ModelAsset,
+    transform: Transform,
+    velocity: Vec3,
+    damage: f32,
+    lifetime: f32,
+) -> Entity {
+    let missile = pool.acquire(commands, PoolKind::Missile);
+    let mut missile_commands = commands.entity(missile.entity);
+    missile_commands.insert((
+        Velocity::linear(velocity),
+        SpatialBundle::from_transform(transform),
+        // Recycled missiles start interpolating from their new position
+        InterpolatedTransform::default(),
         SpaceshipMissile,
         Health::new(MISSILE_HEALTH),
-        CollisionDamage::new(MISSILE_COLLISION_DAMAGE),
+        CollisionDamage::new(damage),
+        Lifetime::new(lifetime).with_fade(LifetimeFade::Shrink, MISSILE_SHRINK_FRACTION),
         DisposableEntity,
     ));
+    // Recycled missiles keep the body and model they were first spawned with
+    if missile.recycled {
+        missile_commands.remove::<FlakWarhead>();
+    } else {
+        missile_commands.insert(MovingObjectBundle {
+            velocity: Velocity::linear(velocity),
+            collider: model.collider(),
+            ..default()
+        }).with_children(|parent| {
+            parent.spawn(model.scene_bundle());
+        });
+    }
+    missile.entity
+}
+
+
+fn burst_flak_missiles(
+    mut commands: Commands,
+    mut lifetime_expired_event_reader: EventReader<LifetimeExpiredEvent>,
+    query: Query<&FlakWarhead>,
+    mut pool: ResMut<EntityPool>,
+    scene_assets: Res<SceneAssets>,
+    mut particle_burst_event_writer: EventWriter<ParticleBurstEvent>,
+    mut stat_event_writer: EventWriter<StatEvent>,
+) {
+    for &LifetimeExpiredEvent { entity, translation } in lifetime_expired_event_reader.read() {
+        let Ok(warhead) = query.get(entity) else { continue };
+        let model = scene_assets.get_random_bullet();
+        for i in 0..warhead.fragments {
+            let direction = Quat::from_rotation_y(TAU * i as f32 / warhead.fragments as f32) * Vec3::Z;
+            spawn_missile(
+                &mut commands,
+                &mut pool,
+                model,
+                model.transform(Transform::from_translation(translation)),
+                direction * warhead.speed,
+                warhead.damage,
+                FLAK_FRAGMENT_LIFETIME,
+            );
+        }
+        // Fragments are shots of their own, since each of them can hit
+        stat_event_writer.send(StatEvent::ShotsFired(warhead.fragments));
+        particle_burst_event_writer.send(ParticleBurstEvent {
+            kind: ParticleEffectKind::MissileImpact,
+            translation,
+            direction: Vec3::ZERO,
+        });
+    }
 }
 
 
@@ -174,3 +415,77 @@ fn check_spaceship(mut next_state: ResMut<NextState<GameState>>, query: Query<()
         next_state.set(GameState::GameOver);
     }
 }
+
+
+fn thaw_spaceship(mut commands: Commands, mut query: Query<(Entity, &mut Chilled)>, time: Res<Time>) {
+    for (entity, mut chilled) in query.iter_mut() {
+        chilled.timer.tick(time.delta());
+        if chilled.timer.finished() {
+            commands.entity(entity).remove::<Chilled>();
+        }
+    }
+}
+
+
+// Bursts for missiles hitting something and for the ship itself blowing up
+fn spaceship_destruction_effects(
+    query: Query<(&Health, &GlobalTransform, Has<Spaceship>), (Changed<Health>, Or<(With<Spaceship>, With<SpaceshipMissile>)>)>,
+    mut particle_burst_event_writer: EventWriter<ParticleBurstEvent>,
+) {
+    for (health, transform, is_spaceship) in query.iter() {
+        if health.value > 0. {
+            continue;
+        }
+        particle_burst_event_writer.send(ParticleBurstEvent {
+            kind: if is_spaceship { ParticleEffectKind::ShipDeath } else { ParticleEffectKind::MissileImpact },
+            translation: transform.translation(),
+            direction: Vec3::ZERO,
+        });
+    }
+}
+
+
+// A missile dies on its first hit, so it counts once however many things it touched
+fn count_missile_hits(
+    query: Query<&Health, (Changed<Health>, With<SpaceshipMissile>)>,
+    mut stat_event_writer: EventWriter<StatEvent>,
+) {
+    let hits = query.iter().filter(|health| health.value <= 0.).count() as u32;
+    if hits > 0 {
+        stat_event_writer.send(StatEvent::Hits(hits));
+    }
+}
+
+
+fn god_command(
+    In(_): In<ConsoleArgs>,
+    mut commands: Commands,
+    query: Query<(Entity, Has<Invulnerable>), With<Spaceship>>,
+    mut log: ResMut<ConsoleLog>,
+) {
+    let Ok((entity, invulnerable)) = query.get_single() else {
+        log.error("no spaceship");
+        return;
+    };
+    if invulnerable {
+        commands.entity(entity).remove::<Invulnerable>();
+    } else {
+        commands.entity(entity).insert(Invulnerable);
+    }
+    log.info(format!("god mode {}", if invulnerable { "off" } else { "on" }));
+}
+
+
+fn set_command(In(args): In<ConsoleArgs>, mut query: Query<&mut Health, With<Spaceship>>, mut log: ResMut<ConsoleLog>) {
+    if args.first().map(String::as_str) != Some("health") {
+        log.error("usage: set health <v>");
+        return;
+    }
+    let Some(value) = parse_arg::<f32>(&args, 1, "v", &mut log) else { return };
+    let Ok(mut health) = query.get_single_mut() else {
+        log.error("no spaceship");
+        return;
+    };
+    health.value = value;
+    log.info(format!("ship health set to {}", value));
+}
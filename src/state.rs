@@ -1,8 +1,12 @@
 use bevy::prelude::*;
 
+use crate::console::{ConsoleArgs, ConsoleCommandsExt, ConsoleLog};
+
+
 #[derive(States, Default, Hash, PartialEq, Eq, Clone, Copy, Debug)]
 pub enum GameState {
     #[default]
+    Loading,
     Menu,
     InGame,
     Paused,
@@ -10,6 +14,12 @@ pub enum GameState {
 }
 
 
+impl GameState {
+    const ALL: [GameState; 5] = [Self::Loading, Self::Menu, Self::InGame, Self::Paused, Self::GameOver];
+    const NAMES: [&'static str; 5] = ["Loading", "Menu", "InGame", "Paused", "GameOver"];
+}
+
+
 pub struct StatePlugin;
 
 
@@ -17,6 +27,7 @@ impl Plugin for StatePlugin {
     fn build(&self, app: &mut App) {
         app
             .init_state::<GameState>()
+            .add_console_command("state", "state <GameState> - switches the game state", &GameState::NAMES, state_command)
             .add_systems(Update, (
                 game_state_input_events,
                 transition_to_in_game.run_if(in_state(GameState::GameOver)),
@@ -43,3 +54,17 @@ pub fn game_state_input_events(
 fn transition_to_in_game(mut next_state: ResMut<NextState<GameState>>) {
     next_state.set(GameState::InGame);
 }
+
+
+fn state_command(In(args): In<ConsoleArgs>, mut next_state: ResMut<NextState<GameState>>, mut log: ResMut<ConsoleLog>) {
+    let Some(name) = args.first() else {
+        log.error(format!("missing <GameState>, one of {}", GameState::NAMES.join(", ")));
+        return;
+    };
+    let Some(index) = GameState::NAMES.iter().position(|state| state.eq_ignore_ascii_case(name)) else {
+        log.error(format!("unknown state '{}'", name));
+        return;
+    };
+    next_state.set(GameState::ALL[index]);
+    log.info(format!("state set to {:?}", GameState::ALL[index]));
+}
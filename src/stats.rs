@@ -0,0 +1,274 @@
+use std::{collections::HashMap, fs, path::PathBuf};
+
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::state::GameState;
+use crate::asset_loader::{LoadingAssets, RonAssetLoader};
+use crate::asteroids::AsteroidTier;
+use crate::collision_detection::CollisionDamageEvent;
+use crate::spaceship::Spaceship;
+use crate::hud::GameScore;
+
+const DATA_DIR_NAME: &'static str = "tds-proto";
+const STATS_FILE_NAME: &'static str = "stats.ron";
+const ACHIEVEMENTS_PATH: &'static str = "game.achievements.ron";
+
+
+/// Lifetime statistics of the player, kept across runs
+#[derive(Resource, Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct PlayerStats {
+    pub shots_fired: u32,
+    pub hits: u32,
+    pub asteroids_destroyed: HashMap<AsteroidTier, u32>,
+    pub damage_taken: f32,
+    /// Longest run in seconds
+    pub longest_survival: f32,
+    /// Seconds spent in game, pauses excluded, added up as runs end
+    pub total_play_time: f32,
+    pub runs_played: u32,
+    pub best_score: i32,
+    pub unlocked_achievements: Vec<String>,
+}
+
+
+impl PlayerStats {
+    fn path() -> Option<PathBuf> {
+        dirs::data_dir().map(|dir| dir.join(DATA_DIR_NAME).join(STATS_FILE_NAME))
+    }
+
+    pub fn load() -> Self {
+        let Some(path) = Self::path() else { return Self::default() };
+        let Ok(contents) = fs::read_to_string(&path) else { return Self::default() };
+        ron::from_str(&contents).unwrap_or_else(|err| {
+            error!("Failed to parse {}: {}", path.display(), err);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let Some(path) = Self::path() else { return };
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                if let Some(dir) = path.parent() {
+                    fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+                }
+                fs::write(&path, contents).map_err(|err| err.to_string())
+            });
+        if let Err(err) = result {
+            error!("Failed to save stats to {}: {}", path.display(), err);
+        }
+    }
+
+    /// Share of the fired missiles that hit something, in percent
+    pub fn accuracy(&self) -> f32 {
+        if self.shots_fired == 0 {
+            return 0.;
+        }
+        100. * self.hits as f32 / self.shots_fired as f32
+    }
+
+    pub fn total_asteroids_destroyed(&self) -> u32 {
+        self.asteroids_destroyed.values().sum()
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked_achievements.iter().any(|unlocked| unlocked == id)
+    }
+}
+
+
+/// Gameplay hooks feeding the stats that can't be derived from other events
+#[derive(Event, Debug)]
+pub enum StatEvent {
+    ShotsFired(u32),
+    /// Missiles that died hitting something, each counted once
+    Hits(u32),
+    AsteroidDestroyed(AsteroidTier),
+}
+
+
+#[derive(Deserialize, Clone, Debug)]
+pub enum AchievementCondition {
+    ShotsFired(u32),
+    AsteroidsDestroyed(u32),
+    AsteroidsDestroyedOfTier(AsteroidTier, u32),
+    Accuracy { percent: f32, min_shots: u32 },
+    DamageTaken(f32),
+    SurvivedSeconds(f32),
+    PlayTimeSeconds(f32),
+    RunsPlayed(u32),
+    Score(i32),
+}
+
+
+impl AchievementCondition {
+    fn is_met(&self, stats: &PlayerStats) -> bool {
+        match *self {
+            Self::ShotsFired(count) => stats.shots_fired >= count,
+            Self::AsteroidsDestroyed(count) => stats.total_asteroids_destroyed() >= count,
+            Self::AsteroidsDestroyedOfTier(tier, count) => {
+                stats.asteroids_destroyed.get(&tier).copied().unwrap_or(0) >= count
+            },
+            Self::Accuracy { percent, min_shots } => stats.shots_fired >= min_shots && stats.accuracy() >= percent,
+            Self::DamageTaken(amount) => stats.damage_taken >= amount,
+            Self::SurvivedSeconds(seconds) => stats.longest_survival >= seconds,
+            Self::PlayTimeSeconds(seconds) => stats.total_play_time >= seconds,
+            Self::RunsPlayed(count) => stats.runs_played >= count,
+            Self::Score(score) => stats.best_score >= score,
+        }
+    }
+}
+
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct Achievement {
+    pub id: String,
+    /// Message keys of the name and description
+    pub name_key: String,
+    pub description_key: String,
+    pub condition: AchievementCondition,
+}
+
+
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct Achievements {
+    pub achievements: Vec<Achievement>,
+}
+
+
+#[derive(Resource, Default, Debug)]
+pub struct AchievementAssets {
+    pub achievements: Handle<Achievements>,
+}
+
+
+#[derive(Event, Debug)]
+pub struct AchievementUnlockedEvent {
+    pub name_key: String,
+    pub description_key: String,
+}
+
+
+// Seconds survived in the current run, added to the lifetime play time once the run ends.
+// Kept apart from PlayerStats so ticking it doesn't trigger the achievement checks every frame
+#[derive(Resource, Default, Debug)]
+struct RunTime(f32);
+
+
+pub struct StatsPlugin;
+
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .insert_resource(PlayerStats::load())
+            .init_resource::<RunTime>()
+            .init_resource::<AchievementAssets>()
+            .init_asset::<Achievements>()
+            .register_asset_loader(RonAssetLoader::<Achievements>::new(&["achievements.ron"]))
+            .add_event::<StatEvent>()
+            .add_event::<AchievementUnlockedEvent>()
+            .add_systems(Startup, load_achievements)
+            .add_systems(Update, (
+                track_play_time.run_if(in_state(GameState::InGame)),
+                record_stat_events,
+                unlock_achievements.run_if(resource_changed::<PlayerStats>),
+            ).chain())
+            .add_systems(OnExit(GameState::Menu), reset_run_time)
+            .add_systems(OnEnter(GameState::GameOver), (record_run, reset_run_time).chain())
+            .add_systems(Last, save_stats_on_exit);
+    }
+}
+
+
+fn load_achievements(
+    mut achievement_assets: ResMut<AchievementAssets>,
+    mut loading_assets: ResMut<LoadingAssets>,
+    asset_server: Res<AssetServer>,
+) {
+    achievement_assets.achievements = asset_server.load(ACHIEVEMENTS_PATH);
+    loading_assets.track(&achievement_assets.achievements);
+}
+
+
+fn track_play_time(mut run_time: ResMut<RunTime>, time: Res<Time>) {
+    run_time.0 += time.delta_seconds();
+}
+
+
+fn record_stat_events(
+    mut stat_event_reader: EventReader<StatEvent>,
+    mut collision_damage_event_reader: EventReader<CollisionDamageEvent>,
+    mut stats: ResMut<PlayerStats>,
+    ship_query: Query<(), With<Spaceship>>,
+) {
+    for event in stat_event_reader.read() {
+        match *event {
+            StatEvent::ShotsFired(count) => stats.shots_fired += count,
+            StatEvent::Hits(count) => stats.hits += count,
+            StatEvent::AsteroidDestroyed(tier) => *stats.asteroids_destroyed.entry(tier).or_default() += 1,
+        }
+    }
+    for &CollisionDamageEvent { entity, amount } in collision_damage_event_reader.read() {
+        if ship_query.contains(entity) {
+            stats.damage_taken += amount;
+        }
+    }
+}
+
+
+fn record_run(mut stats: ResMut<PlayerStats>, run_time: Res<RunTime>, score_query: Query<&GameScore>) {
+    stats.runs_played += 1;
+    stats.total_play_time += run_time.0;
+    stats.longest_survival = stats.longest_survival.max(run_time.0);
+    if let Ok(score) = score_query.get_single() {
+        stats.best_score = stats.best_score.max(score.value);
+    }
+    stats.save();
+}
+
+
+fn reset_run_time(mut run_time: ResMut<RunTime>) {
+    run_time.0 = 0.;
+}
+
+
+fn unlock_achievements(
+    mut stats: ResMut<PlayerStats>,
+    achievement_assets: Res<AchievementAssets>,
+    achievements: Res<Assets<Achievements>>,
+    mut achievement_unlocked_event_writer: EventWriter<AchievementUnlockedEvent>,
+) {
+    let Some(achievements) = achievements.get(&achievement_assets.achievements) else { return };
+    let unlocked: Vec<&Achievement> = achievements.achievements.iter()
+        .filter(|achievement| !stats.is_unlocked(&achievement.id) && achievement.condition.is_met(&stats))
+        .collect();
+    if unlocked.is_empty() {
+        return;
+    }
+    for achievement in unlocked {
+        stats.unlocked_achievements.push(achievement.id.clone());
+        achievement_unlocked_event_writer.send(AchievementUnlockedEvent {
+            name_key: achievement.name_key.clone(),
+            description_key: achievement.description_key.clone(),
+        });
+    }
+    stats.save();
+}
+
+
+fn save_stats_on_exit(
+    mut app_exit_event_reader: EventReader<AppExit>,
+    mut stats: ResMut<PlayerStats>,
+    mut run_time: ResMut<RunTime>,
+) {
+    if app_exit_event_reader.read().next().is_some() {
+        // Quitting mid-run still counts the time played so far
+        stats.total_play_time += run_time.0;
+        run_time.0 = 0.;
+        stats.save();
+    }
+}